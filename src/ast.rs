@@ -1,12 +1,15 @@
 pub mod analyzer;
 pub mod argument;
 pub mod assignment;
+pub mod call_graph;
 pub mod definition;
 pub mod expression;
+pub mod iterable;
 pub mod location;
 pub mod module;
 pub mod operator;
+pub mod pattern;
 pub mod print;
 pub mod reassignment;
 pub mod statement;
-pub mod typed_print;
\ No newline at end of file
+pub mod typed_print;
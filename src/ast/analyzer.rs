@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+#[cfg(test)]
+pub mod tests;
+
+use std::collections::{HashMap, HashSet};
 
 use super::argument::{CallArgumentTyped, CallArgumentUntyped};
 use super::module;
@@ -6,12 +9,20 @@ use super::reassignment::{TypedReassignment, TypedReassignmentTarget};
 use crate::ast;
 use crate::ast::argument::{ArgumentTyped, ArgumentUntyped};
 use crate::ast::assignment::TypedAssignment;
-use crate::ast::definition::{DefinitionTyped, DefinitionUntyped, StructField, StructFieldTyped};
+use crate::ast::call_graph::CallGraph;
+use crate::ast::definition::{
+    DefinitionTyped, DefinitionUntyped, EnumVariant, EnumVariantTyped, StructField,
+    StructFieldTyped,
+};
 use crate::ast::expression::{
     StructFieldValue, StructFieldValueTyped, TypedExpression, UntypedExpression,
 };
+use crate::ast::iterable::{TypedIterable, UntypedIterable};
 use crate::ast::module::Module;
-use crate::ast::operator::BinaryOperator;
+use crate::ast::operator::{BinaryOperator, UnaryOperator};
+use crate::ast::pattern::{
+    PatternBinding, TypedMatchArm, TypedPattern, UntypedMatchArm, UntypedPattern, WILDCARD,
+};
 use crate::ast::reassignment::UntypedReassignmentTarget;
 use crate::ast::statement::{TypedStatement, UntypedStatement};
 use crate::lex::location::Location;
@@ -21,11 +32,31 @@ use crate::type_::{Type, UntypedType};
 use ecow::EcoString;
 use vec1::Vec1;
 
+/// Builtin functions, taking two `int` arguments and returning `int`,
+/// which do not fail on overflow
+const INTEGER_BUILTINS: [&str; 6] = [
+    "wrapping_add",
+    "wrapping_sub",
+    "wrapping_mul",
+    "saturating_add",
+    "saturating_sub",
+    "saturating_mul",
+];
+
 #[derive(Debug)]
 pub struct TypeAnalyzer {
     program_state: ProgramState,
 }
 
+/// Hotswap unit, checked against the running program
+#[derive(Debug)]
+pub struct Hotswap {
+    pub module: module::Typed,
+    /// Callers of the functions with changed signatures, which are active on
+    /// the call stack, so that their calls have to be restarted with new code
+    pub restarted_callers: Vec<EcoString>,
+}
+
 impl Default for TypeAnalyzer {
     fn default() -> Self {
         Self::new()
@@ -70,18 +101,33 @@ impl TypeAnalyzer {
         Ok(typed_module)
     }
 
-    /// Converts hotswap function code input to typed AST
+    /// Converts hotswap code input to typed AST
     ///
-    /// # Panics
+    /// Input is a unit of one or more functions and structs, which are type
+    /// checked as a whole, so that they can use each other with new bodies.
+    /// Definitions, that are not present in the running program, are added
+    /// to it. If structs are changed, the rest of the functions are checked
+    /// again against them. On error the program state is left as it was,
+    /// so that the unit is applied all at once or not at all
     ///
-    /// Will panic in case of unexpected state
+    /// Signature of a function can be changed along with all of its callers.
+    /// `call_stack` holds names of the active functions, starting from
+    /// the innermost one, and the outermost call cannot be restarted
     ///
     /// # Errors
     /// Returns `ConvertingError` if:
+    /// - Input has no definitions, or has an enum
     /// - Type checking fails
-    /// - Unknown variable/function reference
-    /// - Type mismatch
-    pub fn handle_hotswap(&mut self, input: &str) -> Result<module::Typed, ConvertingError> {
+    /// - Signature of a function is changed without some of its callers,
+    ///   or while its caller is running in the outermost call
+    /// - Type of a struct field differs from the running one
+    /// - Added struct field has no default value
+    /// - Changed struct breaks one of the rest of the functions
+    pub fn handle_hotswap(
+        &mut self,
+        input: &str,
+        call_stack: &[EcoString],
+    ) -> Result<Hotswap, ConvertingError> {
         let module = parse_module(input);
         let module = match module {
             Ok(module) => module,
@@ -95,70 +141,188 @@ impl TypeAnalyzer {
             }
         };
 
-        let function_name = match module
-            .definitions
-            .as_ref()
-            .ok_or(ConvertingError {
-                error: ConvertingErrorType::InvalidHotswapMultipleDefinitions,
-                location: Location { start: 0, end: 0 },
-            })?
-            .first()
-        {
-            DefinitionUntyped::Function { name, .. } => &name.clone(),
-            DefinitionUntyped::Struct { .. } => {
+        let definitions = module.definitions.as_ref().ok_or(ConvertingError {
+            error: ConvertingErrorType::InvalidHotswapEmpty,
+            location: Location { start: 0, end: 0 },
+        })?;
+
+        for definition in definitions {
+            if let DefinitionUntyped::Enum { name, location, .. } = definition {
                 return Err(ConvertingError {
-                    error: ConvertingErrorType::InvalidHotswapNotFunction,
-                    location: Location { start: 0, end: 0 },
-                })
+                    error: ConvertingErrorType::InvalidHotswapEnum {
+                        enum_name: name.clone(),
+                    },
+                    location: Location {
+                        start: location.start,
+                        end: location.end,
+                    },
+                });
             }
-        };
-
-        if module.definitions.as_ref().unwrap().len() != 1 {
-            return Err(ConvertingError {
-                error: ConvertingErrorType::InvalidHotswapMultipleDefinitions,
-                location: Location { start: 0, end: 0 },
-            });
         }
 
-        let old_function =
-            self.program_state
-                .get_function(function_name)
-                .ok_or(ConvertingError {
-                    error: ConvertingErrorType::FunctionNotDefined {
-                        function_name: function_name.clone(),
-                    },
-                    location: Location { start: 0, end: 0 },
-                })?;
+        let previous_state = self.program_state.clone();
 
-        let typed_module = self.convert_ast_to_tast(&module)?;
+        let hotswap =
+            self.convert_ast_to_tast(&module).and_then(|typed_module| {
+                let restarted_callers = TypeAnalyzer::check_hotswap_definitions(
+                    &previous_state,
+                    &self.program_state,
+                    &typed_module,
+                    call_stack,
+                )?;
 
-        let definitions = typed_module.clone().definitions.ok_or(ConvertingError {
-            error: ConvertingErrorType::InvalidHotswapMultipleDefinitions,
-            location: Location { start: 0, end: 0 },
-        })?;
+                let struct_location = typed_module.definitions.iter().flatten().find_map(
+                    |definition| match definition {
+                        DefinitionTyped::Struct { location, .. } => Some(*location),
+                        DefinitionTyped::Function { .. } | DefinitionTyped::Enum { .. } => None,
+                    },
+                );
 
-        if definitions.len() != 1 {
-            return Err(ConvertingError {
-                error: ConvertingErrorType::InvalidHotswapMultipleDefinitions,
-                location: Location { start: 0, end: 0 },
+                if let Some(struct_location) = struct_location {
+                    self.check_functions_again(&typed_module, struct_location)?;
+                }
+
+                Ok(Hotswap {
+                    module: typed_module,
+                    restarted_callers,
+                })
             });
+
+        if hotswap.is_err() {
+            self.program_state = previous_state;
         }
 
-        let definition = definitions.first();
+        hotswap
+    }
 
-        match definition {
-            DefinitionTyped::Function {
-                location,
-                name,
-                arguments,
-                return_type,
-                ..
-            } => {
-                if name != function_name {
+    /// Takes over the state of the updated program, analyzed by `updated`, if
+    /// its changed definitions can be hotswapped into the running one
+    ///
+    /// Returns callers, which calls have to be restarted, as in `handle_hotswap`
+    ///
+    /// # Errors
+    /// Returns `ConvertingError` if:
+    /// - Changed definition is an enum
+    /// - Signature of the changed function is changed without some of its
+    ///   callers, or while its caller is running in the outermost call
+    /// - Type of the changed struct field differs from the running one
+    /// - Added struct field has no default value
+    pub fn accept_update(
+        &mut self,
+        updated: TypeAnalyzer,
+        changed_definitions: &[&DefinitionTyped],
+        call_stack: &[EcoString],
+    ) -> Result<Vec<EcoString>, ConvertingError> {
+        let changed_module = Module {
+            name: "update".into(),
+            definitions: Vec1::try_from_vec(changed_definitions.iter().copied().cloned().collect())
+                .ok(),
+        };
+
+        let restarted_callers = TypeAnalyzer::check_hotswap_definitions(
+            &self.program_state,
+            &updated.program_state,
+            &changed_module,
+            call_stack,
+        )?;
+        self.program_state = updated.program_state;
+
+        Ok(restarted_callers)
+    }
+
+    /// Checks, that each definition of the hotswap unit can replace the one
+    /// of the running program with `previous_state`, if there is any, in the
+    /// updated program with `updated_state`
+    ///
+    /// Function with changed signature needs all of its callers in the unit,
+    /// and their active calls are restarted, except for the outermost one
+    /// in `call_stack`, which has no call to roll back to
+    ///
+    /// Returns callers, which calls have to be restarted
+    ///
+    /// # Errors
+    /// Returns `ConvertingError` if definition is an enum, or if it does
+    /// not match the running one
+    fn check_hotswap_definitions(
+        previous_state: &ProgramState,
+        updated_state: &ProgramState,
+        unit: &module::Typed,
+        call_stack: &[EcoString],
+    ) -> Result<Vec<EcoString>, ConvertingError> {
+        let unit_functions = unit
+            .definitions
+            .iter()
+            .flatten()
+            .filter_map(|definition| match definition {
+                DefinitionTyped::Function { name, .. } => Some(name),
+                DefinitionTyped::Struct { .. } | DefinitionTyped::Enum { .. } => None,
+            })
+            .collect::<HashSet<_>>();
+        let call_graph = CallGraph::new(previous_state.functions.values());
+        let (outermost_call, restartable_calls) = match call_stack.split_last() {
+            Some((outermost_call, restartable_calls)) => (Some(outermost_call), restartable_calls),
+            None => (None, call_stack),
+        };
+        let mut restarted_callers = Vec::new();
+
+        for definition in unit.definitions.iter().flatten() {
+            match definition {
+                DefinitionTyped::Function { name, .. } => {
+                    let Some(old_function) = previous_state.get_function(name) else {
+                        continue;
+                    };
+                    let Err(error) =
+                        TypeAnalyzer::check_hotswap_signature(&old_function, definition)
+                    else {
+                        continue;
+                    };
+
+                    // NOTE: code of the callers, that stay in the program, passes old arguments
+                    let callers = call_graph.get_callers(name);
+                    let missing_callers = callers
+                        .iter()
+                        .filter(|caller| !unit_functions.contains(caller))
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    if !missing_callers.is_empty() {
+                        return Err(ConvertingError {
+                            location: error.location,
+                            error: ConvertingErrorType::InvalidHotswapCallersNotSwapped {
+                                callers: missing_callers,
+                                error: Box::new(error),
+                            },
+                        });
+                    }
+
+                    // NOTE: active calls of the callers go on with their old code
+                    if let Some(caller) = outermost_call.filter(|caller| callers.contains(caller)) {
+                        return Err(ConvertingError {
+                            location: error.location,
+                            error: ConvertingErrorType::InvalidHotswapActiveCaller {
+                                caller_name: caller.clone(),
+                                error: Box::new(error),
+                            },
+                        });
+                    }
+
+                    for caller in callers {
+                        if restartable_calls.contains(&caller)
+                            && !restarted_callers.contains(&caller)
+                        {
+                            restarted_callers.push(caller);
+                        }
+                    }
+                }
+                DefinitionTyped::Struct { name, .. } => {
+                    if let Some(old_struct) = previous_state.get_struct(name) {
+                        TypeAnalyzer::check_hotswap_fields(updated_state, old_struct, definition)?;
+                    }
+                }
+                DefinitionTyped::Enum { name, location, .. } => {
                     return Err(ConvertingError {
-                        error: ConvertingErrorType::InvalidHotswapNameMismatch {
-                            expected: function_name.clone(),
-                            found: name.clone(),
+                        error: ConvertingErrorType::InvalidHotswapEnum {
+                            enum_name: name.clone(),
                         },
                         location: Location {
                             start: location.start,
@@ -166,12 +330,50 @@ impl TypeAnalyzer {
                         },
                     });
                 }
+            }
+        }
+
+        Ok(restarted_callers)
+    }
+
+    /// Checks, that live instances of the struct can be migrated to its new
+    /// version: fields, that are present in both versions, must keep their types,
+    /// and added fields must have a default value
+    ///
+    /// # Errors
+    /// Returns `ConvertingError` if type of a field is changed, or if added
+    /// field has no default value
+    fn check_hotswap_fields(
+        updated_state: &ProgramState,
+        old_struct: &DefinitionTyped,
+        new_struct: &DefinitionTyped,
+    ) -> Result<(), ConvertingError> {
+        let (
+            DefinitionTyped::Struct {
+                fields: old_fields, ..
+            },
+            DefinitionTyped::Struct {
+                name,
+                location,
+                fields: new_fields,
+            },
+        ) = (old_struct, new_struct)
+        else {
+            return Ok(());
+        };
 
-                if !TypeAnalyzer::compare_types(&old_function.get_return_type()?, return_type) {
+        for new_field in new_fields.iter().flatten() {
+            let old_field = old_fields
+                .iter()
+                .flatten()
+                .find(|old_field| old_field.name == new_field.name);
+
+            let Some(old_field) = old_field else {
+                if !updated_state.has_default_value(&new_field.type_, &mut HashSet::new()) {
                     return Err(ConvertingError {
-                        error: ConvertingErrorType::InvalidHotswapReturnTypeMismatch {
-                            expected: old_function.get_return_type()?.clone(),
-                            found: return_type.clone(),
+                        error: ConvertingErrorType::InvalidHotswapFieldWithoutDefault {
+                            struct_name: name.clone(),
+                            field_name: new_field.name.clone(),
                         },
                         location: Location {
                             start: location.start,
@@ -179,52 +381,151 @@ impl TypeAnalyzer {
                         },
                     });
                 }
+                continue;
+            };
+
+            if !TypeAnalyzer::compare_types(&old_field.type_, &new_field.type_) {
+                return Err(ConvertingError {
+                    error: ConvertingErrorType::InvalidHotswapFieldTypeMismatch {
+                        struct_name: name.clone(),
+                        field_name: new_field.name.clone(),
+                        expected: old_field.type_.clone(),
+                        found: new_field.type_.clone(),
+                    },
+                    location: Location {
+                        start: location.start,
+                        end: location.end,
+                    },
+                });
+            }
+        }
 
-                let old_args_count = old_function.get_arguments()?.as_ref().map_or(0, Vec1::len);
-                let new_args_count = arguments.as_ref().map_or(0, Vec1::len);
+        Ok(())
+    }
+
+    /// Checks again the functions of the running program, that are not in
+    /// the hotswap unit, as structs they use may be changed by it
+    ///
+    /// # Errors
+    /// Returns `ConvertingError` at `struct_location` in the unit, if one of
+    /// the functions does not type check anymore
+    fn check_functions_again(
+        &mut self,
+        unit: &module::Typed,
+        struct_location: ast::location::Location,
+    ) -> Result<(), ConvertingError> {
+        let unit_functions = unit
+            .definitions
+            .iter()
+            .flatten()
+            .filter_map(|definition| match definition {
+                DefinitionTyped::Function { name, .. } => Some(name),
+                DefinitionTyped::Struct { .. } | DefinitionTyped::Enum { .. } => None,
+            })
+            .collect::<HashSet<_>>();
+
+        let mut definitions = self
+            .program_state
+            .function_definitions
+            .iter()
+            .filter(|(name, _)| !unit_functions.contains(name))
+            .map(|(name, definition)| (name.clone(), definition.clone()))
+            .collect::<Vec<_>>();
+        definitions.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+        for (function_name, definition) in definitions {
+            let function = Module {
+                name: unit.name.clone(),
+                definitions: Some(Vec1::new(definition)),
+            };
+
+            self.convert_ast_to_tast(&function)
+                .map_err(|error| ConvertingError {
+                    error: ConvertingErrorType::InvalidHotswapBreaksFunction {
+                        function_name,
+                        error: Box::new(error),
+                    },
+                    location: Location {
+                        start: struct_location.start,
+                        end: struct_location.end,
+                    },
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks, that the new version of the function can replace the running one,
+    /// so that its callers stay correct: return type, amount and types of
+    /// the arguments must match
+    ///
+    /// # Errors
+    /// Returns `ConvertingError` if signatures of the functions differ
+    fn check_hotswap_signature(
+        old_function: &DefinitionTyped,
+        new_function: &DefinitionTyped,
+    ) -> Result<(), ConvertingError> {
+        let DefinitionTyped::Function {
+            location,
+            arguments,
+            return_type,
+            ..
+        } = new_function
+        else {
+            return Err(ConvertingError {
+                error: ConvertingErrorType::InvalidHotswapNotFunction,
+                location: Location { start: 0, end: 0 },
+            });
+        };
+
+        if !TypeAnalyzer::compare_types(&old_function.get_return_type()?, return_type) {
+            return Err(ConvertingError {
+                error: ConvertingErrorType::InvalidHotswapReturnTypeMismatch {
+                    expected: old_function.get_return_type()?.clone(),
+                    found: return_type.clone(),
+                },
+                location: Location {
+                    start: location.start,
+                    end: location.end,
+                },
+            });
+        }
+
+        let old_args_count = old_function.get_arguments()?.as_ref().map_or(0, Vec1::len);
+        let new_args_count = arguments.as_ref().map_or(0, Vec1::len);
+
+        if old_args_count != new_args_count {
+            return Err(ConvertingError {
+                error: ConvertingErrorType::InvalidHotswapArgumentCountMismatch {
+                    expected: old_args_count,
+                    found: new_args_count,
+                },
+                location: Location {
+                    start: location.start,
+                    end: location.end,
+                },
+            });
+        }
 
-                if old_args_count != new_args_count {
+        if let (Some(old_args), Some(new_args)) = (&old_function.get_arguments()?, arguments) {
+            for (index, (old_arg, new_arg)) in old_args.iter().zip(new_args.iter()).enumerate() {
+                if !TypeAnalyzer::compare_types(&old_arg.type_, &new_arg.type_) {
                     return Err(ConvertingError {
-                        error: ConvertingErrorType::InvalidHotswapArgumentCountMismatch {
-                            expected: old_args_count,
-                            found: new_args_count,
+                        error: ConvertingErrorType::InvalidHotswapArgumentTypeMismatch {
+                            argument_index: index,
+                            expected: old_arg.type_.clone(),
+                            found: new_arg.type_.clone(),
                         },
                         location: Location {
-                            start: location.start,
-                            end: location.end,
+                            start: new_arg.location.start,
+                            end: new_arg.location.end,
                         },
                     });
                 }
-
-                if let (Some(old_args), Some(new_args)) =
-                    (&old_function.get_arguments()?, arguments)
-                {
-                    for (index, (old_arg, new_arg)) in
-                        old_args.iter().zip(new_args.iter()).enumerate()
-                    {
-                        if !TypeAnalyzer::compare_types(&old_arg.type_, &new_arg.type_) {
-                            return Err(ConvertingError {
-                                error: ConvertingErrorType::InvalidHotswapArgumentTypeMismatch {
-                                    argument_index: index,
-                                    expected: old_arg.type_.clone(),
-                                    found: new_arg.type_.clone(),
-                                },
-                                location: Location {
-                                    start: new_arg.location.start,
-                                    end: new_arg.location.end,
-                                },
-                            });
-                        }
-                    }
-                }
-
-                Ok(typed_module)
             }
-            DefinitionTyped::Struct { .. } => Err(ConvertingError {
-                error: ConvertingErrorType::InvalidHotswapNotFunction,
-                location: Location { start: 0, end: 0 },
-            }),
         }
+
+        Ok(())
     }
 
     /// Converts AST to typed AST
@@ -286,6 +587,44 @@ impl TypeAnalyzer {
                         self.program_state
                             .add_struct(name.clone(), typed_struct_without_fields);
                     }
+                    DefinitionUntyped::Enum { location, name, .. } => {
+                        let typed_enum_without_variants = DefinitionTyped::Enum {
+                            location: *location,
+                            name: name.clone(),
+                            variants: None,
+                        };
+
+                        self.program_state
+                            .add_enum(name.clone(), typed_enum_without_variants);
+                    }
+                }
+            }
+
+            // NOTE: variants are resolved before function bodies,
+            // so that enums can be constructed and matched before their definition
+            for definition in definitions {
+                if let DefinitionUntyped::Enum {
+                    location,
+                    name,
+                    variants,
+                } = definition
+                {
+                    let typed_variants = variants
+                        .as_ref()
+                        .map(|variants| {
+                            variants
+                                .clone()
+                                .try_mapped(|variant| self.convert_enum_variant(&variant))
+                        })
+                        .transpose()?;
+
+                    let typed_enum = DefinitionTyped::Enum {
+                        location: *location,
+                        name: name.clone(),
+                        variants: typed_variants,
+                    };
+
+                    self.program_state.add_enum(name.clone(), typed_enum);
                 }
             }
         }
@@ -329,18 +668,38 @@ impl TypeAnalyzer {
                             })
                             .transpose()?;
 
+                        let return_type = typed_function_definition.get_return_type()?;
+
+                        if return_type != Type::Void
+                            && !typed_body
+                                .as_ref()
+                                .is_some_and(|body| Self::always_terminates(body))
+                        {
+                            return Err(ConvertingError {
+                                error: ConvertingErrorType::MissingReturn {
+                                    function_name: name.clone(),
+                                },
+                                location: Location {
+                                    start: location.start,
+                                    end: location.end,
+                                },
+                            });
+                        }
+
                         let typed_function_definition_with_body = DefinitionTyped::Function {
                             name: name.clone(),
                             location: *location,
                             arguments: typed_function_definition.get_arguments()?,
                             body: typed_body,
-                            return_type: typed_function_definition.get_return_type()?,
+                            return_type,
                         };
 
                         self.program_state.add_function(
                             name.clone(),
                             typed_function_definition_with_body.clone(),
                         );
+                        self.program_state
+                            .add_function_definition(name.clone(), definition.clone());
 
                         typed_function_definition_with_body
                     }
@@ -369,6 +728,9 @@ impl TypeAnalyzer {
 
                         typed_struct
                     }
+                    DefinitionUntyped::Enum { name, .. } => {
+                        self.program_state.get_enum(name).unwrap().clone()
+                    }
                 };
 
                 match &mut typed_definitions {
@@ -412,6 +774,26 @@ impl TypeAnalyzer {
         })
     }
 
+    fn convert_enum_variant(
+        &mut self,
+        variant: &EnumVariant,
+    ) -> Result<EnumVariantTyped, ConvertingError> {
+        let typed_fields = variant
+            .fields
+            .as_ref()
+            .map(|fields| {
+                fields
+                    .clone()
+                    .try_mapped(|field| self.convert_struct_field(&field))
+            })
+            .transpose()?;
+
+        Ok(EnumVariantTyped {
+            name: variant.name.clone(),
+            fields: typed_fields,
+        })
+    }
+
     fn convert_struct_field_value(
         &mut self,
         struct_field_value: &StructFieldValue,
@@ -491,6 +873,9 @@ impl TypeAnalyzer {
 
                 self.program_state
                     .add_variable(assignment.variable_name.clone(), resolved_type.clone());
+                if !matches!(typed_value.get_type(), Type::Optional { .. } | Type::Nil) {
+                    self.program_state.mark_non_nil(&assignment.variable_name);
+                }
 
                 Ok(TypedStatement::Assignment(TypedAssignment {
                     location: assignment.location,
@@ -587,6 +972,15 @@ impl TypeAnalyzer {
                     });
                 }
 
+                if let TypedReassignmentTarget::Variable { name, .. } = &typed_target {
+                    match typed_new_value.get_type() {
+                        Type::Optional { .. } | Type::Nil => {
+                            self.program_state.forget_nil_check(name);
+                        }
+                        _ => self.program_state.mark_non_nil(name),
+                    }
+                }
+
                 Ok(TypedStatement::Reassignment(TypedReassignment {
                     location: reassignment.location,
                     target: typed_target.clone(),
@@ -594,8 +988,40 @@ impl TypeAnalyzer {
                     type_: typed_target.get_type(),
                 }))
             }
-            UntypedStatement::Loop { body, location } => {
+            UntypedStatement::Loop {
+                condition,
+                body,
+                location,
+            } => {
+                if let Some(body) = body {
+                    self.forget_nil_checks_of_reassigned(body);
+                }
+
+                let typed_condition = condition
+                    .as_ref()
+                    .map(|condition| self.convert_expression_to_typed(condition))
+                    .transpose()?;
+
+                if let Some(typed_condition) = &typed_condition {
+                    if *typed_condition.get_type() != Type::Boolean {
+                        return Err(ConvertingError {
+                            error: ConvertingErrorType::TypeMismatch {
+                                expected: Type::Boolean,
+                                found: typed_condition.get_type().clone(),
+                            },
+                            location: Location {
+                                start: location.start,
+                                end: location.end,
+                            },
+                        });
+                    }
+                }
+
                 let saved_scope = self.program_state.create_scope();
+                if let Some(typed_condition) = &typed_condition {
+                    self.mark_nil_checked_variables(typed_condition, true);
+                }
+                self.program_state.enter_loop();
                 let typed_body = body
                     .as_ref()
                     .map(|statements| {
@@ -603,10 +1029,48 @@ impl TypeAnalyzer {
                             .clone()
                             .try_mapped(|statement| self.convert_statement_to_typed(&statement))
                     })
-                    .transpose()?;
+                    .transpose();
+                self.program_state.exit_loop();
                 self.program_state.restore_scope(saved_scope);
+                let typed_body = typed_body?;
 
                 Ok(TypedStatement::Loop {
+                    condition: typed_condition.map(Box::new),
+                    body: typed_body,
+                    location: *location,
+                })
+            }
+            UntypedStatement::For {
+                variable_name,
+                iterable,
+                body,
+                location,
+            } => {
+                if let Some(body) = body {
+                    self.forget_nil_checks_of_reassigned(body);
+                }
+
+                let typed_iterable = self.convert_iterable_to_typed(iterable)?;
+
+                let saved_scope = self.program_state.create_scope();
+                self.program_state
+                    .add_variable(variable_name.clone(), typed_iterable.get_element_type());
+                self.program_state.enter_loop();
+                let typed_body = body
+                    .as_ref()
+                    .map(|statements| {
+                        statements
+                            .clone()
+                            .try_mapped(|statement| self.convert_statement_to_typed(&statement))
+                    })
+                    .transpose();
+                self.program_state.exit_loop();
+                self.program_state.restore_scope(saved_scope);
+                let typed_body = typed_body?;
+
+                Ok(TypedStatement::For {
+                    variable_name: variable_name.clone(),
+                    iterable: typed_iterable,
                     body: typed_body,
                     location: *location,
                 })
@@ -633,6 +1097,7 @@ impl TypeAnalyzer {
                 }
 
                 let saved_scope = self.program_state.create_scope();
+                self.mark_nil_checked_variables(&typed_condition, true);
                 let typed_if_body = if_body
                     .as_ref()
                     .map(|statements| {
@@ -644,6 +1109,7 @@ impl TypeAnalyzer {
                 self.program_state.restore_scope(saved_scope);
 
                 let saved_scope = self.program_state.create_scope();
+                self.mark_nil_checked_variables(&typed_condition, false);
                 let typed_else_body = else_body
                     .as_ref()
                     .map(|statements| {
@@ -654,6 +1120,17 @@ impl TypeAnalyzer {
                     .transpose()?;
                 self.program_state.restore_scope(saved_scope);
 
+                for body in [if_body, else_body].into_iter().flatten() {
+                    self.forget_nil_checks_of_reassigned(body);
+                }
+
+                // NOTE: code after `if (x == nil) { return }` is only reachable with non nil `x`
+                if typed_else_body.is_none()
+                    && typed_if_body.as_ref().is_some_and(Self::is_terminating)
+                {
+                    self.mark_nil_checked_variables(&typed_condition, false);
+                }
+
                 Ok(TypedStatement::If {
                     condition: Box::new(typed_condition),
                     if_body: typed_if_body,
@@ -661,9 +1138,129 @@ impl TypeAnalyzer {
                     location: *location,
                 })
             }
-            UntypedStatement::Break { location } => Ok(TypedStatement::Break {
-                location: *location,
-            }),
+            UntypedStatement::Match {
+                value,
+                arms,
+                location,
+            } => {
+                let typed_value = self.convert_expression_to_typed(value)?;
+
+                let Type::Enum { name: enum_name } = typed_value.get_type() else {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::NotMatchable {
+                            found: typed_value.get_type().clone(),
+                        },
+                        location: Location {
+                            start: typed_value.get_location().start,
+                            end: typed_value.get_location().end,
+                        },
+                    });
+                };
+                let enum_name = enum_name.clone();
+
+                let mut uncovered_variants = self
+                    .resolve_enum_variants(&enum_name, location.start, location.end)?
+                    .iter()
+                    .map(|variant| variant.name.clone())
+                    .collect::<Vec<_>>();
+                let mut has_wildcard = false;
+
+                let typed_arms = arms
+                    .as_ref()
+                    .map(|arms| {
+                        arms.clone().try_mapped(|arm| {
+                            let typed_arm = self.convert_match_arm(&arm, &enum_name)?;
+
+                            let is_reachable = match &typed_arm.pattern {
+                                TypedPattern::Variant { variant_name, .. } => {
+                                    let index = uncovered_variants
+                                        .iter()
+                                        .position(|variant| variant == variant_name);
+                                    index
+                                        .map(|index| uncovered_variants.remove(index))
+                                        .is_some()
+                                }
+                                TypedPattern::Wildcard { .. } => {
+                                    uncovered_variants.clear();
+                                    !has_wildcard
+                                }
+                            };
+
+                            if !is_reachable || has_wildcard {
+                                return Err(ConvertingError {
+                                    error: ConvertingErrorType::UnreachableMatchArm,
+                                    location: Location {
+                                        start: arm.location.start,
+                                        end: arm.location.end,
+                                    },
+                                });
+                            }
+
+                            has_wildcard =
+                                matches!(typed_arm.pattern, TypedPattern::Wildcard { .. });
+
+                            Ok(typed_arm)
+                        })
+                    })
+                    .transpose()?;
+
+                if !uncovered_variants.is_empty() {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::NonExhaustiveMatch {
+                            enum_name,
+                            missing_variants: uncovered_variants,
+                        },
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                }
+
+                if let Some(arms) = arms {
+                    for arm in arms {
+                        if let Some(body) = &arm.body {
+                            self.forget_nil_checks_of_reassigned(body);
+                        }
+                    }
+                }
+
+                Ok(TypedStatement::Match {
+                    value: Box::new(typed_value),
+                    arms: typed_arms,
+                    location: *location,
+                })
+            }
+            UntypedStatement::Break { location } => {
+                if !self.program_state.is_inside_loop() {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::BreakOutsideLoop,
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                }
+
+                Ok(TypedStatement::Break {
+                    location: *location,
+                })
+            }
+            UntypedStatement::Continue { location } => {
+                if !self.program_state.is_inside_loop() {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::ContinueOutsideLoop,
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                }
+
+                Ok(TypedStatement::Continue {
+                    location: *location,
+                })
+            }
             UntypedStatement::Return { location, value } => {
                 let typed_value = value
                     .as_ref()
@@ -710,13 +1307,138 @@ impl TypeAnalyzer {
             UntypedStatement::Todo { location } => Ok(TypedStatement::Todo {
                 location: *location,
             }),
-            UntypedStatement::Panic { location } => Ok(TypedStatement::Panic {
-                location: *location,
-            }),
-            UntypedStatement::Exit { location } => Ok(TypedStatement::Exit {
+            UntypedStatement::Panic { location, message } => {
+                let typed_message = self.convert_expression_to_typed(message)?;
+
+                if *typed_message.get_type() != Type::String {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::TypeMismatch {
+                            expected: Type::String,
+                            found: typed_message.get_type().clone(),
+                        },
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                }
+
+                Ok(TypedStatement::Panic {
+                    location: *location,
+                    message: Box::new(typed_message),
+                })
+            }
+            UntypedStatement::Exit { location, code } => {
+                let typed_code = self.convert_expression_to_typed(code)?;
+
+                if *typed_code.get_type() != Type::Int {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::TypeMismatch {
+                            expected: Type::Int,
+                            found: typed_code.get_type().clone(),
+                        },
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                }
+
+                Ok(TypedStatement::Exit {
+                    location: *location,
+                    code: Box::new(typed_code),
+                })
+            }
+        }
+    }
+
+    fn convert_match_arm(
+        &mut self,
+        arm: &UntypedMatchArm,
+        enum_name: &EcoString,
+    ) -> Result<TypedMatchArm, ConvertingError> {
+        let saved_scope = self.program_state.create_scope();
+
+        let typed_pattern = match &arm.pattern {
+            UntypedPattern::Variant {
+                location,
+                variant_name,
+                bindings,
+            } => {
+                let variant = self.resolve_enum_variant(
+                    enum_name,
+                    variant_name,
+                    location.start,
+                    location.end,
+                )?;
+
+                // NOTE: bindings can be omitted to ignore all fields of the variant
+                let typed_bindings = match (bindings, &variant.fields) {
+                    (None, _) => None,
+                    (Some(bindings), Some(fields)) if bindings.len() == fields.len() => {
+                        let typed_bindings = bindings
+                            .iter()
+                            .zip(fields.iter())
+                            .map(|(name, field)| PatternBinding {
+                                name: name.clone(),
+                                type_: field.type_.clone(),
+                            })
+                            .collect();
+                        Vec1::try_from_vec(typed_bindings).ok()
+                    }
+                    (Some(bindings), fields) => {
+                        return Err(ConvertingError {
+                            error: ConvertingErrorType::VariantFieldsAmountMismatch {
+                                variant_name: variant_name.clone(),
+                                expected: fields.as_ref().map_or(0, Vec1::len),
+                                found: bindings.len(),
+                            },
+                            location: Location {
+                                start: location.start,
+                                end: location.end,
+                            },
+                        });
+                    }
+                };
+
+                TypedPattern::Variant {
+                    location: *location,
+                    variant_name: variant_name.clone(),
+                    bindings: typed_bindings,
+                }
+            }
+            UntypedPattern::Wildcard { location } => TypedPattern::Wildcard {
                 location: *location,
-            }),
+            },
+        };
+
+        if let TypedPattern::Variant {
+            bindings: Some(bindings),
+            ..
+        } = &typed_pattern
+        {
+            for binding in bindings.iter().filter(|binding| binding.name != WILDCARD) {
+                self.program_state
+                    .add_variable(binding.name.clone(), binding.type_.clone());
+            }
         }
+
+        let typed_body = arm
+            .body
+            .as_ref()
+            .map(|statements| {
+                statements
+                    .clone()
+                    .try_mapped(|statement| self.convert_statement_to_typed(&statement))
+            })
+            .transpose();
+        self.program_state.restore_scope(saved_scope);
+
+        Ok(TypedMatchArm {
+            location: arm.location,
+            pattern: typed_pattern,
+            body: typed_body?,
+        })
     }
 
     fn convert_expression_to_typed(
@@ -771,6 +1493,17 @@ impl TypeAnalyzer {
                     type_: Type::Char,
                 })
             }
+            UntypedExpression::BooleanLiteral { location, value } => {
+                Ok(TypedExpression::BooleanLiteral {
+                    location: *location,
+                    value: *value,
+                    type_: Type::Boolean,
+                })
+            }
+            UntypedExpression::NilLiteral { location } => Ok(TypedExpression::NilLiteral {
+                location: *location,
+                type_: Type::Nil,
+            }),
             UntypedExpression::VariableValue { location, name } => {
                 let resolved_type =
                     self.resolve_variable_type(name, location.start, location.end)?;
@@ -787,7 +1520,18 @@ impl TypeAnalyzer {
                 right,
             } => {
                 let typed_left = self.convert_expression_to_typed(left)?;
-                let typed_right = self.convert_expression_to_typed(right)?;
+
+                // NOTE: right side of `&&` and `||` is evaluated only if left one
+                // did not decide the result, so nil checks from it can be used
+                let saved_scope = self.program_state.create_scope();
+                match operator {
+                    BinaryOperator::And => self.mark_nil_checked_variables(&typed_left, true),
+                    BinaryOperator::Or => self.mark_nil_checked_variables(&typed_left, false),
+                    _ => {}
+                }
+                let typed_right = self.convert_expression_to_typed(right);
+                self.program_state.restore_scope(saved_scope);
+                let typed_right = typed_right?;
 
                 let result_type = TypeAnalyzer::check_type_of_binary_operation(
                     typed_left.get_type(),
@@ -804,6 +1548,26 @@ impl TypeAnalyzer {
                     type_: result_type,
                 })
             }
+            UntypedExpression::UnaryOperation {
+                location,
+                operator,
+                operand,
+            } => {
+                let typed_operand = self.convert_expression_to_typed(operand)?;
+
+                let result_type = TypeAnalyzer::check_type_of_unary_operation(
+                    typed_operand.get_type(),
+                    *operator,
+                    start_expression_location,
+                    end_expression_location,
+                )?;
+                Ok(TypedExpression::UnaryOperation {
+                    location: *location,
+                    operator: *operator,
+                    operand: Box::new(typed_operand),
+                    type_: result_type,
+                })
+            }
             UntypedExpression::FunctionCall {
                 location,
                 function_name,
@@ -868,6 +1632,79 @@ impl TypeAnalyzer {
                     type_: function_type,
                 })
             }
+            UntypedExpression::EnumInitialization {
+                location,
+                enum_name,
+                variant_name,
+                arguments,
+            } => {
+                let variant = self.resolve_enum_variant(
+                    enum_name,
+                    variant_name,
+                    location.start,
+                    location.end,
+                )?;
+
+                let expected_amount = variant.fields.as_ref().map_or(0, Vec1::len);
+                let found_amount = arguments.as_ref().map_or(0, Vec1::len);
+
+                if expected_amount != found_amount {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::VariantFieldsAmountMismatch {
+                            variant_name: variant_name.clone(),
+                            expected: expected_amount,
+                            found: found_amount,
+                        },
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                }
+
+                let typed_arguments = arguments
+                    .as_ref()
+                    .map(|arguments| {
+                        arguments
+                            .iter()
+                            .zip(variant.fields.iter().flatten())
+                            .map(|(argument, field)| {
+                                let typed_value =
+                                    self.convert_expression_to_typed(&argument.value)?;
+
+                                if !Self::compare_types(&field.type_, typed_value.get_type()) {
+                                    return Err(ConvertingError {
+                                        error: ConvertingErrorType::TypeMismatch {
+                                            expected: field.type_.clone(),
+                                            found: typed_value.get_type().clone(),
+                                        },
+                                        location: Location {
+                                            start: argument.location.start,
+                                            end: argument.location.end,
+                                        },
+                                    });
+                                }
+
+                                Ok(CallArgumentTyped {
+                                    location: argument.location,
+                                    type_: typed_value.get_type().clone(),
+                                    value: typed_value,
+                                })
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .transpose()?;
+
+                Ok(TypedExpression::EnumInitialization {
+                    location: *location,
+                    enum_name: enum_name.clone(),
+                    variant_name: variant_name.clone(),
+                    arguments: typed_arguments,
+                    type_: Type::Enum {
+                        name: enum_name.clone(),
+                    },
+                })
+            }
             UntypedExpression::StructFieldAccess {
                 location,
                 struct_name,
@@ -1010,6 +1847,8 @@ impl TypeAnalyzer {
                     })
                     .transpose()?;
 
+                self.check_fields_initialized(struct_name, typed_fields.as_ref(), *location)?;
+
                 Ok(TypedExpression::StructInitialization {
                     location: *location,
                     type_: resolved_type.clone(),
@@ -1020,23 +1859,23 @@ impl TypeAnalyzer {
     }
 
     fn resolve_array_element_type(
-        &mut self,
-        array_name: &EcoString,
-        start_location: u32,
-        end_location: u32,
-    ) -> Result<Type, ConvertingError> {
-        let array_type =
-            self.program_state
-                .get_variable_type(array_name)
-                .ok_or(ConvertingError {
-                    error: ConvertingErrorType::VariableNotDefined {
-                        variable_name: array_name.clone(),
-                    },
-                    location: crate::lex::location::Location {
-                        start: start_location,
-                        end: end_location,
-                    },
-                })?;
+        &mut self,
+        array_name: &EcoString,
+        start_location: u32,
+        end_location: u32,
+    ) -> Result<Type, ConvertingError> {
+        let array_type = self
+            .program_state
+            .get_checked_variable_type(array_name)
+            .ok_or(ConvertingError {
+                error: ConvertingErrorType::VariableNotDefined {
+                    variable_name: array_name.clone(),
+                },
+                location: crate::lex::location::Location {
+                    start: start_location,
+                    end: end_location,
+                },
+            })?;
         match array_type {
             Type::Array { type_ } => Ok(*type_.clone()),
             _ => Err(ConvertingError {
@@ -1046,7 +1885,7 @@ impl TypeAnalyzer {
                     },
                     found: self
                         .program_state
-                        .get_variable_type(array_name)
+                        .get_checked_variable_type(array_name)
                         .unwrap()
                         .clone(),
                 },
@@ -1058,6 +1897,49 @@ impl TypeAnalyzer {
         }
     }
 
+    /// Checks, that struct initialization sets each field, that cannot be
+    /// left with the default value
+    ///
+    /// # Errors
+    /// Returns `ConvertingError` if such field is not initialized
+    fn check_fields_initialized(
+        &self,
+        struct_name: &EcoString,
+        initialized_fields: Option<&Vec1<StructFieldValueTyped>>,
+        location: ast::location::Location,
+    ) -> Result<(), ConvertingError> {
+        let Some(DefinitionTyped::Struct { fields, .. }) =
+            self.program_state.get_struct(struct_name)
+        else {
+            return Ok(());
+        };
+
+        for field in fields.iter().flatten() {
+            let is_initialized = initialized_fields.is_some_and(|initialized| {
+                initialized.iter().any(|value| value.name == field.name)
+            });
+
+            if !is_initialized
+                && !self
+                    .program_state
+                    .has_default_value(&field.type_, &mut HashSet::new())
+            {
+                return Err(ConvertingError {
+                    error: ConvertingErrorType::FieldNotInitialized {
+                        struct_name: struct_name.clone(),
+                        field_name: field.name.clone(),
+                    },
+                    location: Location {
+                        start: location.start,
+                        end: location.end,
+                    },
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     fn resolve_struct_field_type(
         &self,
         struct_name: &EcoString,
@@ -1122,11 +2004,26 @@ impl TypeAnalyzer {
         start_location: u32,
         end_location: u32,
     ) -> Result<Type, ConvertingError> {
+        if let Some(Type::Optional { .. }) = self
+            .program_state
+            .get_checked_variable_type(struct_variable_name)
+        {
+            return Err(ConvertingError {
+                error: ConvertingErrorType::PossiblyNilFieldAccess {
+                    variable_name: struct_variable_name.clone(),
+                },
+                location: crate::lex::location::Location {
+                    start: start_location,
+                    end: end_location,
+                },
+            });
+        }
+
         if let Type::Custom {
             name: struct_def_name,
         } = self
             .program_state
-            .get_variable_type(struct_variable_name)
+            .get_checked_variable_type(struct_variable_name)
             .ok_or(ConvertingError {
                 error: ConvertingErrorType::VariableNotDefined {
                     variable_name: struct_variable_name.clone(),
@@ -1193,7 +2090,7 @@ impl TypeAnalyzer {
                     },
                     found: self
                         .program_state
-                        .get_variable_type(struct_variable_name)
+                        .get_checked_variable_type(struct_variable_name)
                         .unwrap()
                         .clone(),
                 },
@@ -1205,6 +2102,50 @@ impl TypeAnalyzer {
         }
     }
 
+    fn resolve_enum_variants(
+        &self,
+        enum_name: &EcoString,
+        start_location: u32,
+        end_location: u32,
+    ) -> Result<Vec<EnumVariantTyped>, ConvertingError> {
+        match self.program_state.get_enum(enum_name) {
+            Some(DefinitionTyped::Enum { variants, .. }) => {
+                Ok(variants.iter().flatten().cloned().collect())
+            }
+            _ => Err(ConvertingError {
+                error: ConvertingErrorType::EnumNotDefined {
+                    enum_name: enum_name.clone(),
+                },
+                location: Location {
+                    start: start_location,
+                    end: end_location,
+                },
+            }),
+        }
+    }
+
+    fn resolve_enum_variant(
+        &self,
+        enum_name: &EcoString,
+        variant_name: &EcoString,
+        start_location: u32,
+        end_location: u32,
+    ) -> Result<EnumVariantTyped, ConvertingError> {
+        self.resolve_enum_variants(enum_name, start_location, end_location)?
+            .into_iter()
+            .find(|variant| variant.name == *variant_name)
+            .ok_or(ConvertingError {
+                error: ConvertingErrorType::VariantNotFound {
+                    enum_name: enum_name.clone(),
+                    variant_name: variant_name.clone(),
+                },
+                location: Location {
+                    start: start_location,
+                    end: end_location,
+                },
+            })
+    }
+
     fn resolve_function_return_type(
         &mut self,
         function_name: &EcoString,
@@ -1242,18 +2183,18 @@ impl TypeAnalyzer {
         start_location: u32,
         end_location: u32,
     ) -> Result<Type, ConvertingError> {
-        let variable_type =
-            self.program_state
-                .get_variable_type(variable_name)
-                .ok_or(ConvertingError {
-                    error: ConvertingErrorType::VariableNotDefined {
-                        variable_name: variable_name.clone(),
-                    },
-                    location: crate::lex::location::Location {
-                        start: start_location,
-                        end: end_location,
-                    },
-                })?;
+        let variable_type = self
+            .program_state
+            .get_checked_variable_type(variable_name)
+            .ok_or(ConvertingError {
+                error: ConvertingErrorType::VariableNotDefined {
+                    variable_name: variable_name.clone(),
+                },
+                location: crate::lex::location::Location {
+                    start: start_location,
+                    end: end_location,
+                },
+            })?;
         Ok(variable_type.clone())
     }
 
@@ -1301,7 +2242,16 @@ impl TypeAnalyzer {
             },
 
             BinaryOperator::Equal | BinaryOperator::NotEqual => {
-                if left_type == right_type {
+                let is_nil_check = matches!(
+                    (left_type, right_type),
+                    (Type::Nil, Type::Custom { .. } | Type::Enum { .. })
+                        | (Type::Custom { .. } | Type::Enum { .. }, Type::Nil)
+                );
+
+                if Self::compare_types(left_type, right_type)
+                    || Self::compare_types(right_type, left_type)
+                    || is_nil_check
+                {
                     Ok(Type::Boolean)
                 } else {
                     Err(ConvertingError {
@@ -1318,7 +2268,12 @@ impl TypeAnalyzer {
             | BinaryOperator::SubtractionInt
             | BinaryOperator::MultipicationInt
             | BinaryOperator::DivisionInt
-            | BinaryOperator::Modulo => match (left_type, right_type) {
+            | BinaryOperator::Modulo
+            | BinaryOperator::BitwiseAnd
+            | BinaryOperator::BitwiseOr
+            | BinaryOperator::BitwiseXor
+            | BinaryOperator::ShiftLeft
+            | BinaryOperator::ShiftRight => match (left_type, right_type) {
                 (Type::Int, Type::Int) => Ok(Type::Int),
                 _ => Err(ConvertingError {
                     error: ConvertingErrorType::IntOperationInvalidType,
@@ -1347,6 +2302,94 @@ impl TypeAnalyzer {
         }
     }
 
+    fn convert_iterable_to_typed(
+        &mut self,
+        iterable: &UntypedIterable,
+    ) -> Result<TypedIterable, ConvertingError> {
+        match iterable {
+            UntypedIterable::Range {
+                location,
+                start,
+                end,
+            } => {
+                let typed_start = self.convert_expression_to_typed(start)?;
+                let typed_end = self.convert_expression_to_typed(end)?;
+
+                for typed_bound in [&typed_start, &typed_end] {
+                    if *typed_bound.get_type() != Type::Int {
+                        return Err(ConvertingError {
+                            error: ConvertingErrorType::TypeMismatch {
+                                expected: Type::Int,
+                                found: typed_bound.get_type().clone(),
+                            },
+                            location: Location {
+                                start: typed_bound.get_location().start,
+                                end: typed_bound.get_location().end,
+                            },
+                        });
+                    }
+                }
+
+                Ok(TypedIterable::Range {
+                    location: *location,
+                    start: Box::new(typed_start),
+                    end: Box::new(typed_end),
+                })
+            }
+            UntypedIterable::Array { location, array } => {
+                let typed_array = self.convert_expression_to_typed(array)?;
+
+                let Type::Array { type_ } = typed_array.get_type() else {
+                    return Err(ConvertingError {
+                        error: ConvertingErrorType::NotIterable {
+                            found: typed_array.get_type().clone(),
+                        },
+                        location: Location {
+                            start: location.start,
+                            end: location.end,
+                        },
+                    });
+                };
+
+                Ok(TypedIterable::Array {
+                    location: *location,
+                    element_type: *type_.clone(),
+                    array: Box::new(typed_array),
+                })
+            }
+        }
+    }
+
+    fn check_type_of_unary_operation(
+        operand_type: &Type,
+        operator: UnaryOperator,
+        start_location: u32,
+        end_location: u32,
+    ) -> Result<Type, ConvertingError> {
+        let err_location = crate::lex::location::Location {
+            start: start_location,
+            end: end_location,
+        };
+
+        match (operator, operand_type) {
+            (UnaryOperator::Not, Type::Boolean) => Ok(Type::Boolean),
+            (UnaryOperator::NegationInt, Type::Int) => Ok(Type::Int),
+            (UnaryOperator::NegationFloat, Type::Float) => Ok(Type::Float),
+            (UnaryOperator::Not, _) => Err(ConvertingError {
+                error: ConvertingErrorType::InvalidNotOperation,
+                location: err_location,
+            }),
+            (UnaryOperator::NegationInt, _) => Err(ConvertingError {
+                error: ConvertingErrorType::IntNegationInvalidType,
+                location: err_location,
+            }),
+            (UnaryOperator::NegationFloat, _) => Err(ConvertingError {
+                error: ConvertingErrorType::FloatNegationInvalidType,
+                location: err_location,
+            }),
+        }
+    }
+
     fn convert_call_argument_to_typed(
         &mut self,
         function_name: &EcoString,
@@ -1360,7 +2403,7 @@ impl TypeAnalyzer {
         let function_name_str = function_name.as_str();
 
         if function_name_str == "print" || function_name_str == "println" {
-            if arguments.as_ref().map_or(true, |args| args.len() != 1) {
+            if arguments.as_ref().is_none_or(|args| args.len() != 1) {
                 return Err(ConvertingError {
                     error: ConvertingErrorType::InvalidArgumentsAmount {
                         expected: 1,
@@ -1383,7 +2426,7 @@ impl TypeAnalyzer {
                 });
             }
         } else if function_name_str == "append" {
-            if arguments.as_ref().map_or(true, |args| args.len() != 2) {
+            if arguments.as_ref().is_none_or(|args| args.len() != 2) {
                 return Err(ConvertingError {
                     error: ConvertingErrorType::InvalidArgumentsAmount {
                         expected: 2,
@@ -1418,7 +2461,7 @@ impl TypeAnalyzer {
                         .convert_expression_to_typed(&first_arg.value)?
                         .get_type()
                     {
-                        if **element_type != *typed_argument.get_type() {
+                        if !Self::compare_types(element_type, typed_argument.get_type()) {
                             return Err(ConvertingError {
                                 error: ConvertingErrorType::TypeMismatch {
                                     expected: *element_type.clone(),
@@ -1442,7 +2485,7 @@ impl TypeAnalyzer {
                 }
             }
         } else if function_name_str == "pop" {
-            if arguments.as_ref().map_or(true, |args| args.len() != 1) {
+            if arguments.as_ref().is_none_or(|args| args.len() != 1) {
                 return Err(ConvertingError {
                     error: ConvertingErrorType::InvalidArgumentsAmount {
                         expected: 1,
@@ -1467,6 +2510,32 @@ impl TypeAnalyzer {
                     });
                 }
             }
+        } else if INTEGER_BUILTINS.contains(&function_name_str) {
+            if arguments.as_ref().is_none_or(|args| args.len() != 2) {
+                return Err(ConvertingError {
+                    error: ConvertingErrorType::InvalidArgumentsAmount {
+                        expected: 2,
+                        found: arguments.map_or(0, vec1::Vec1::len),
+                    },
+                    location: Location {
+                        start: location.start,
+                        end: location.end,
+                    },
+                });
+            }
+
+            if !matches!(typed_argument.get_type(), Type::Int) {
+                return Err(ConvertingError {
+                    error: ConvertingErrorType::TypeMismatch {
+                        expected: Type::Int,
+                        found: typed_argument.get_type().clone(),
+                    },
+                    location: Location {
+                        start: location.start,
+                        end: location.end,
+                    },
+                });
+            }
         } else {
             let function_def =
                 self.program_state
@@ -1496,7 +2565,7 @@ impl TypeAnalyzer {
                     });
                 }
 
-                if expected_args[i].type_ != *typed_argument.get_type() {
+                if !Self::compare_types(&expected_args[i].type_, typed_argument.get_type()) {
                     return Err(ConvertingError {
                         error: ConvertingErrorType::TypeMismatch {
                             expected: expected_args[i].type_.clone(),
@@ -1541,6 +2610,10 @@ impl TypeAnalyzer {
             UntypedType::String => Ok(Type::String),
             UntypedType::Char => Ok(Type::Char),
             UntypedType::Custom { name } => {
+                if self.program_state.get_enum(name).is_some() {
+                    return Ok(Type::Enum { name: name.clone() });
+                }
+
                 if self.program_state.get_struct(name).is_none() {
                     return Err(ConvertingError {
                         error: ConvertingErrorType::StructNotDefined {
@@ -1562,26 +2635,199 @@ impl TypeAnalyzer {
                 })
             }
             UntypedType::Boolean => Ok(Type::Boolean),
+            UntypedType::Optional { type_ } => {
+                let inner_type =
+                    self.convert_untyped_to_typed(type_, start_location, end_location)?;
+                Ok(Type::Optional {
+                    type_: Box::new(inner_type),
+                })
+            }
         }
     }
 
     fn compare_types(expected: &Type, found: &Type) -> bool {
         match (expected, found) {
             (Type::Custom { name: name1 }, Type::Custom { name: name2 }) => name1 == name2,
-            (Type::Array { type_: type1 }, Type::Array { type_: type2 }) => {
+            // NOTE: arrays are shared by reference, so element types must match exactly,
+            // otherwise `[]T?` alias would allow storing nil into `[]T`
+            (Type::Array { type_: type1 }, Type::Array { type_: type2 }) => type1 == type2,
+            (Type::Optional { type_: type1 }, Type::Optional { type_: type2 }) => {
                 Self::compare_types(type1, type2)
             }
+            (Type::Optional { .. }, Type::Nil) => true,
+            (Type::Optional { type_ }, found_type) => Self::compare_types(type_, found_type),
             (expected_type, found_type) => expected_type == found_type,
         }
     }
+
+    fn is_terminating(statements: &Vec1<TypedStatement>) -> bool {
+        matches!(
+            statements.last(),
+            TypedStatement::Return { .. }
+                | TypedStatement::Break { .. }
+                | TypedStatement::Continue { .. }
+                | TypedStatement::Panic { .. }
+                | TypedStatement::Exit { .. }
+        )
+    }
+
+    /// Returns variables, that are known to be not nil, when `condition` evaluates to `outcome`
+    fn get_nil_checked_variables(condition: &TypedExpression, outcome: bool) -> Vec<EcoString> {
+        match condition {
+            TypedExpression::BinaryOperation {
+                operator: operator @ (BinaryOperator::Equal | BinaryOperator::NotEqual),
+                left,
+                right,
+                ..
+            } => {
+                if (*operator == BinaryOperator::NotEqual) != outcome {
+                    return Vec::new();
+                }
+
+                match (left.as_ref(), right.as_ref()) {
+                    (
+                        TypedExpression::VariableValue { name, .. },
+                        TypedExpression::NilLiteral { .. },
+                    )
+                    | (
+                        TypedExpression::NilLiteral { .. },
+                        TypedExpression::VariableValue { name, .. },
+                    ) => {
+                        vec![name.clone()]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            TypedExpression::BinaryOperation {
+                operator: operator @ (BinaryOperator::And | BinaryOperator::Or),
+                left,
+                right,
+                ..
+            } => {
+                if (*operator == BinaryOperator::And) != outcome {
+                    return Vec::new();
+                }
+
+                let mut variables = Self::get_nil_checked_variables(left, outcome);
+                variables.extend(Self::get_nil_checked_variables(right, outcome));
+                variables
+            }
+            TypedExpression::UnaryOperation {
+                operator: UnaryOperator::Not,
+                operand,
+                ..
+            } => Self::get_nil_checked_variables(operand, !outcome),
+            _ => Vec::new(),
+        }
+    }
+
+    fn mark_nil_checked_variables(&mut self, condition: &TypedExpression, outcome: bool) {
+        for variable_name in Self::get_nil_checked_variables(condition, outcome) {
+            self.program_state.mark_non_nil(&variable_name);
+        }
+    }
+
+    /// Drops nil checks of variables, that are reassigned in the given statements,
+    /// as those do not hold after the loop iteration or the conditional branch
+    fn forget_nil_checks_of_reassigned(&mut self, statements: &[UntypedStatement]) {
+        for statement in statements {
+            match statement {
+                UntypedStatement::Reassignment(reassignment) => {
+                    if let UntypedReassignmentTarget::Variable { name, .. } = &reassignment.target {
+                        self.program_state.forget_nil_check(name);
+                    }
+                }
+                UntypedStatement::Loop { body, .. } | UntypedStatement::For { body, .. } => {
+                    if let Some(body) = body {
+                        self.forget_nil_checks_of_reassigned(body);
+                    }
+                }
+                UntypedStatement::If {
+                    if_body, else_body, ..
+                } => {
+                    for body in [if_body, else_body].into_iter().flatten() {
+                        self.forget_nil_checks_of_reassigned(body);
+                    }
+                }
+                UntypedStatement::Match { arms, .. } => {
+                    for body in arms.iter().flatten().filter_map(|arm| arm.body.as_ref()) {
+                        self.forget_nil_checks_of_reassigned(body);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether execution of the statements never reaches their end: each path
+    /// returns, stops the program, backs off or loops forever
+    fn always_terminates(statements: &[TypedStatement]) -> bool {
+        statements.iter().any(|statement| match statement {
+            TypedStatement::Return { .. }
+            | TypedStatement::Todo { .. }
+            | TypedStatement::Panic { .. }
+            | TypedStatement::Exit { .. } => true,
+            TypedStatement::If {
+                if_body: Some(if_body),
+                else_body: Some(else_body),
+                ..
+            } => Self::always_terminates(if_body) && Self::always_terminates(else_body),
+            // NOTE: arms of the match are exhaustive, as it is checked beforehand
+            TypedStatement::Match {
+                arms: Some(arms), ..
+            } => arms.iter().all(|arm| {
+                arm.body
+                    .as_ref()
+                    .is_some_and(|body| Self::always_terminates(body))
+            }),
+            TypedStatement::Loop {
+                condition: None,
+                body,
+                ..
+            } => !body.as_ref().is_some_and(|body| Self::contains_break(body)),
+            _ => false,
+        })
+    }
+
+    /// Whether the statements contain `break` of the loop they are in,
+    /// not of the nested ones
+    fn contains_break(statements: &[TypedStatement]) -> bool {
+        statements.iter().any(|statement| match statement {
+            TypedStatement::Break { .. } => true,
+            TypedStatement::If {
+                if_body, else_body, ..
+            } => [if_body, else_body]
+                .into_iter()
+                .flatten()
+                .any(|body| Self::contains_break(body)),
+            TypedStatement::Match { arms, .. } => arms
+                .iter()
+                .flatten()
+                .filter_map(|arm| arm.body.as_ref())
+                .any(|body| Self::contains_break(body)),
+            _ => false,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProgramState {
     variables: HashMap<EcoString, Type>,
+    non_nil_variables: HashSet<EcoString>,
     functions: HashMap<EcoString, DefinitionTyped>,
+    /// Untyped definition of each function, so that it can be checked again,
+    /// when structs are hotswapped
+    function_definitions: HashMap<EcoString, DefinitionUntyped>,
     structs: HashMap<EcoString, DefinitionTyped>,
+    enums: HashMap<EcoString, DefinitionTyped>,
     current_function_name: EcoString,
+    loop_depth: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Scope {
+    variables: HashMap<EcoString, Type>,
+    non_nil_variables: HashSet<EcoString>,
 }
 
 impl Default for ProgramState {
@@ -1595,26 +2841,57 @@ impl ProgramState {
     pub fn new() -> Self {
         ProgramState {
             variables: HashMap::new(),
+            non_nil_variables: HashSet::new(),
             functions: HashMap::new(),
+            function_definitions: HashMap::new(),
             structs: HashMap::new(),
+            enums: HashMap::new(),
             current_function_name: "".into(),
+            loop_depth: 0,
         }
     }
 
     fn add_variable(&mut self, name: EcoString, type_: Type) {
+        self.non_nil_variables.remove(&name);
         self.variables.insert(name, type_);
     }
 
+    /// Returns declared variable type, optional types are kept as is
     fn get_variable_type(&self, name: &EcoString) -> Option<&Type> {
         self.variables.get(name)
     }
 
+    /// Returns variable type as seen at the current point of the program,
+    /// optional type is unwrapped, if the variable was checked against nil
+    fn get_checked_variable_type(&self, name: &EcoString) -> Option<&Type> {
+        match self.variables.get(name)? {
+            Type::Optional { type_ } if self.non_nil_variables.contains(name) => Some(type_),
+            type_ => Some(type_),
+        }
+    }
+
+    fn mark_non_nil(&mut self, name: &EcoString) {
+        if let Some(Type::Optional { .. }) = self.variables.get(name) {
+            self.non_nil_variables.insert(name.clone());
+        }
+    }
+
+    fn forget_nil_check(&mut self, name: &EcoString) {
+        self.non_nil_variables.remove(name);
+    }
+
     fn add_function(&mut self, name: EcoString, definition: DefinitionTyped) {
         if let DefinitionTyped::Function { .. } = definition {
             self.functions.insert(name, definition);
         }
     }
 
+    fn add_function_definition(&mut self, name: EcoString, definition: DefinitionUntyped) {
+        if let DefinitionUntyped::Function { .. } = definition {
+            self.function_definitions.insert(name, definition);
+        }
+    }
+
     fn get_function(&self, name: &EcoString) -> Option<DefinitionTyped> {
         if name == "print" || name == "println" {
             let function = DefinitionTyped::Function {
@@ -1674,6 +2951,21 @@ impl ProgramState {
             };
             return Some(function);
         }
+        if INTEGER_BUILTINS.contains(&name.as_str()) {
+            let argument = ArgumentTyped {
+                name: EcoString::default(),
+                location: ast::location::Location { start: 0, end: 0 },
+                type_: Type::Int,
+            };
+            let function = DefinitionTyped::Function {
+                name: name.clone(),
+                location: ast::location::Location { start: 0, end: 0 },
+                arguments: Some(Vec1::try_from(vec![argument.clone(), argument]).unwrap()),
+                body: None,
+                return_type: Type::Int,
+            };
+            return Some(function);
+        }
         self.functions.get(name).cloned()
     }
 
@@ -1687,16 +2979,67 @@ impl ProgramState {
         self.structs.get(name)
     }
 
+    /// Whether value of the type can be left uninitialized: enums have no
+    /// default value, so neither do structs, that hold them
+    fn has_default_value(&self, type_: &Type, visited_structs: &mut HashSet<EcoString>) -> bool {
+        match type_ {
+            Type::Enum { .. } => false,
+            Type::Custom { name } => {
+                // NOTE: struct, that is already being checked, is decided by its other fields
+                if !visited_structs.insert(name.clone()) {
+                    return true;
+                }
+
+                let Some(DefinitionTyped::Struct { fields, .. }) = self.get_struct(name) else {
+                    return true;
+                };
+
+                fields
+                    .iter()
+                    .flatten()
+                    .all(|field| self.has_default_value(&field.type_, visited_structs))
+            }
+            _ => true,
+        }
+    }
+
+    fn add_enum(&mut self, name: EcoString, definition: DefinitionTyped) {
+        if let DefinitionTyped::Enum { .. } = definition {
+            self.enums.insert(name, definition);
+        }
+    }
+
+    fn get_enum(&self, name: &EcoString) -> Option<&DefinitionTyped> {
+        self.enums.get(name)
+    }
+
     fn clear_variables(&mut self) {
         self.variables.clear();
+        self.non_nil_variables.clear();
+    }
+
+    fn create_scope(&self) -> Scope {
+        Scope {
+            variables: self.variables.clone(),
+            non_nil_variables: self.non_nil_variables.clone(),
+        }
+    }
+
+    fn restore_scope(&mut self, saved_scope: Scope) {
+        self.variables = saved_scope.variables;
+        self.non_nil_variables = saved_scope.non_nil_variables;
+    }
+
+    fn enter_loop(&mut self) {
+        self.loop_depth += 1;
     }
 
-    fn create_scope(&self) -> HashMap<EcoString, Type> {
-        self.variables.clone()
+    fn exit_loop(&mut self) {
+        self.loop_depth -= 1;
     }
 
-    fn restore_scope(&mut self, saved_variables: HashMap<EcoString, Type>) {
-        self.variables = saved_variables;
+    fn is_inside_loop(&self) -> bool {
+        self.loop_depth > 0
     }
 
     fn set_current_function_name(&mut self, name: &EcoString) {
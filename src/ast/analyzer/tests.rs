@@ -0,0 +1,174 @@
+use ecow::EcoString;
+
+use crate::{
+    parse::error::{ConvertingError, ConvertingErrorType},
+    type_::Type,
+};
+
+use super::{Hotswap, TypeAnalyzer};
+
+const SHAPE: &str = "
+enum shape {
+    circle(radius float)
+    point
+}
+";
+
+fn expect_error(src: &str) -> ConvertingErrorType {
+    TypeAnalyzer::new()
+        .analyze_input(src)
+        .expect_err("should not type check")
+        .error
+}
+
+fn expect_ok(src: &str) {
+    let result = TypeAnalyzer::new().analyze_input(src);
+
+    assert!(result.is_ok(), "should type check: {result:?}");
+}
+
+/// Analyzes `src` and hotswaps `unit` into it, while functions of `call_stack` are running
+fn analyze_hotswap(
+    src: &str,
+    unit: &str,
+    call_stack: &[EcoString],
+) -> (TypeAnalyzer, Result<Hotswap, ConvertingError>) {
+    let mut analyzer = TypeAnalyzer::new();
+    analyzer.analyze_input(src).expect("should type check");
+
+    let hotswap = analyzer.handle_hotswap(unit, call_stack);
+    (analyzer, hotswap)
+}
+
+#[test]
+fn test_enum_field_must_be_initialized() {
+    let holder = format!("{SHAPE}\nstruct holder {{\n    value shape\n    count int\n}}\n");
+
+    assert_eq!(
+        expect_error(&format!(
+            "{holder}\nfunc main() {{ var h holder = holder{{count: 1}} }}"
+        )),
+        ConvertingErrorType::FieldNotInitialized {
+            struct_name: "holder".into(),
+            field_name: "value".into(),
+        }
+    );
+    expect_ok(&format!(
+        "{holder}\nfunc main() {{ var h holder = holder{{value: shape::point}} }}"
+    ));
+
+    // NOTE: default value of a nested struct would leave its enum uninitialized
+    let boxed = format!("{holder}\nstruct boxed {{\n    inner holder\n}}\n");
+    assert_eq!(
+        expect_error(&format!(
+            "{boxed}\nfunc main() {{ var b boxed = boxed{{}} }}"
+        )),
+        ConvertingErrorType::FieldNotInitialized {
+            struct_name: "boxed".into(),
+            field_name: "inner".into(),
+        }
+    );
+
+    expect_ok(&format!(
+        "{SHAPE}\nstruct holder {{\n    value shape?\n}}\nfunc main() {{ var h holder = holder{{}} }}"
+    ));
+}
+
+#[test]
+fn test_hotswap_struct_fields() {
+    let src = "struct person {\n    age int\n}\nfunc main() { var p person = person{age: 1} }";
+
+    let (_, hotswap) = analyze_hotswap(src, "struct person {\n    age string\n}", &[]);
+    assert_eq!(
+        hotswap.expect_err("type of the field is changed").error,
+        ConvertingErrorType::InvalidHotswapFieldTypeMismatch {
+            struct_name: "person".into(),
+            field_name: "age".into(),
+            expected: Type::Int,
+            found: Type::String,
+        }
+    );
+
+    let (_, hotswap) = analyze_hotswap(
+        &format!("{SHAPE}\n{src}"),
+        "struct person {\n    age int\n    favourite shape\n}",
+        &[],
+    );
+    assert_eq!(
+        hotswap.expect_err("added field has no default value").error,
+        ConvertingErrorType::InvalidHotswapFieldWithoutDefault {
+            struct_name: "person".into(),
+            field_name: "favourite".into(),
+        }
+    );
+
+    let (_, hotswap) = analyze_hotswap(
+        src,
+        "struct person {\n    age int\n    scores []int\n}",
+        &[],
+    );
+    assert!(hotswap.is_ok(), "should hotswap: {hotswap:?}");
+}
+
+#[test]
+fn test_hotswap_unit_rolls_back_on_error() {
+    let src = "func helper() int { return 1 }\nfunc main() { println(helper()) }";
+    let unit = "func helper() int { return 2 }\nfunc broken() int { return \"two\" }";
+
+    let mut analyzer = TypeAnalyzer::new();
+    analyzer.analyze_input(src).expect("should type check");
+    let helper = format!(
+        "{:?}",
+        analyzer.program_state.get_function(&"helper".into())
+    );
+
+    let hotswap = analyzer.handle_hotswap(unit, &[]);
+    assert!(hotswap.is_err(), "`broken` should not type check");
+
+    assert_eq!(
+        format!(
+            "{:?}",
+            analyzer.program_state.get_function(&"helper".into())
+        ),
+        helper
+    );
+    assert!(analyzer
+        .program_state
+        .get_function(&"broken".into())
+        .is_none());
+}
+
+#[test]
+fn test_hotswap_signature_with_callers() {
+    let src = "func helper(x int) int { return x }
+func compute() int { return helper(1) }
+func main() { println(compute()) }";
+    let helper = "func helper(x int, y int) int { return x + y }";
+    let unit = format!("{helper}\nfunc compute() int {{ return helper(1, 2) }}");
+
+    let (_, hotswap) = analyze_hotswap(src, helper, &[]);
+    assert!(matches!(
+        hotswap.expect_err("caller `compute` is not in the unit").error,
+        ConvertingErrorType::InvalidHotswapCallersNotSwapped { callers, .. }
+            if callers == vec![EcoString::from("compute")]
+    ));
+
+    let (_, hotswap) = analyze_hotswap(src, &unit, &["compute".into()]);
+    assert!(matches!(
+        hotswap.expect_err("call of `compute` cannot be restarted").error,
+        ConvertingErrorType::InvalidHotswapActiveCaller { caller_name, .. }
+            if caller_name == "compute"
+    ));
+
+    let (_, hotswap) = analyze_hotswap(src, &unit, &["compute".into(), "main".into()]);
+    assert_eq!(
+        hotswap.expect("should hotswap").restarted_callers,
+        vec![EcoString::from("compute")]
+    );
+
+    let (_, hotswap) = analyze_hotswap(src, &unit, &["main".into()]);
+    assert!(hotswap
+        .expect("should hotswap")
+        .restarted_callers
+        .is_empty());
+}
@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use ecow::EcoString;
+
+use super::{
+    definition::DefinitionTyped, expression::TypedExpression, iterable::TypedIterable,
+    reassignment::TypedReassignmentTarget, statement::TypedStatement,
+};
+
+/// Functions, called by each function of the program
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    callees: HashMap<EcoString, HashSet<EcoString>>,
+}
+
+impl CallGraph {
+    #[must_use]
+    pub fn new<'a>(definitions: impl IntoIterator<Item = &'a DefinitionTyped>) -> Self {
+        let callees = definitions
+            .into_iter()
+            .filter_map(|definition| match definition {
+                DefinitionTyped::Function { name, body, .. } => {
+                    let mut callees = HashSet::new();
+                    collect_statements_calls(body.as_deref().unwrap_or_default(), &mut callees);
+
+                    Some((name.clone(), callees))
+                }
+                DefinitionTyped::Struct { .. } | DefinitionTyped::Enum { .. } => None,
+            })
+            .collect();
+
+        Self { callees }
+    }
+
+    /// Functions, that call the given one, sorted by name
+    #[must_use]
+    pub fn get_callers(&self, function_name: &EcoString) -> Vec<EcoString> {
+        let mut callers = self
+            .callees
+            .iter()
+            .filter(|(_, callees)| callees.contains(function_name))
+            .map(|(caller, _)| caller.clone())
+            .collect::<Vec<_>>();
+        callers.sort();
+
+        callers
+    }
+}
+
+fn collect_statements_calls(statements: &[TypedStatement], callees: &mut HashSet<EcoString>) {
+    for statement in statements {
+        match statement {
+            TypedStatement::Expression(expression) => collect_expression_calls(expression, callees),
+            TypedStatement::Assignment(assignment) => {
+                collect_expression_calls(&assignment.value, callees);
+            }
+            TypedStatement::Reassignment(reassignment) => {
+                if let TypedReassignmentTarget::ArrayAccess {
+                    index_expression, ..
+                } = &reassignment.target
+                {
+                    collect_expression_calls(index_expression, callees);
+                }
+                collect_expression_calls(&reassignment.new_value, callees);
+            }
+            TypedStatement::Loop {
+                condition, body, ..
+            } => {
+                if let Some(condition) = condition {
+                    collect_expression_calls(condition, callees);
+                }
+                collect_statements_calls(body.as_deref().unwrap_or_default(), callees);
+            }
+            TypedStatement::For { iterable, body, .. } => {
+                match iterable {
+                    TypedIterable::Range { start, end, .. } => {
+                        collect_expression_calls(start, callees);
+                        collect_expression_calls(end, callees);
+                    }
+                    TypedIterable::Array { array, .. } => {
+                        collect_expression_calls(array, callees);
+                    }
+                }
+                collect_statements_calls(body.as_deref().unwrap_or_default(), callees);
+            }
+            TypedStatement::If {
+                condition,
+                if_body,
+                else_body,
+                ..
+            } => {
+                collect_expression_calls(condition, callees);
+                collect_statements_calls(if_body.as_deref().unwrap_or_default(), callees);
+                collect_statements_calls(else_body.as_deref().unwrap_or_default(), callees);
+            }
+            TypedStatement::Match { value, arms, .. } => {
+                collect_expression_calls(value, callees);
+
+                for arm in arms.iter().flatten() {
+                    collect_statements_calls(arm.body.as_deref().unwrap_or_default(), callees);
+                }
+            }
+            TypedStatement::Return {
+                value: Some(value), ..
+            } => collect_expression_calls(value, callees),
+            TypedStatement::Panic { message, .. } => collect_expression_calls(message, callees),
+            TypedStatement::Exit { code, .. } => collect_expression_calls(code, callees),
+            TypedStatement::Return { value: None, .. }
+            | TypedStatement::Break { .. }
+            | TypedStatement::Continue { .. }
+            | TypedStatement::Todo { .. } => {}
+        }
+    }
+}
+
+fn collect_expression_calls(expression: &TypedExpression, callees: &mut HashSet<EcoString>) {
+    match expression {
+        TypedExpression::FunctionCall {
+            function_name,
+            arguments,
+            ..
+        } => {
+            callees.insert(function_name.clone());
+
+            for argument in arguments.iter().flatten() {
+                collect_expression_calls(&argument.value, callees);
+            }
+        }
+        TypedExpression::EnumInitialization { arguments, .. } => {
+            for argument in arguments.iter().flatten() {
+                collect_expression_calls(&argument.value, callees);
+            }
+        }
+        TypedExpression::ArrayElementAccess {
+            index_expression, ..
+        } => collect_expression_calls(index_expression, callees),
+        TypedExpression::ArrayInitialization { elements, .. } => {
+            for element in elements.iter().flatten() {
+                collect_expression_calls(element, callees);
+            }
+        }
+        TypedExpression::StructInitialization { fields, .. } => {
+            for field in fields.iter().flatten() {
+                collect_expression_calls(&field.value, callees);
+            }
+        }
+        TypedExpression::BinaryOperation { left, right, .. } => {
+            collect_expression_calls(left, callees);
+            collect_expression_calls(right, callees);
+        }
+        TypedExpression::UnaryOperation { operand, .. } => {
+            collect_expression_calls(operand, callees);
+        }
+        TypedExpression::IntLiteral { .. }
+        | TypedExpression::FloatLiteral { .. }
+        | TypedExpression::StringLiteral { .. }
+        | TypedExpression::CharLiteral { .. }
+        | TypedExpression::BooleanLiteral { .. }
+        | TypedExpression::NilLiteral { .. }
+        | TypedExpression::VariableValue { .. }
+        | TypedExpression::StructFieldAccess { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecow::EcoString;
+
+    use crate::ast::analyzer::TypeAnalyzer;
+
+    use super::CallGraph;
+
+    fn build_call_graph(src: &str) -> CallGraph {
+        let module = TypeAnalyzer::new()
+            .analyze_input(src)
+            .expect("should type check");
+
+        CallGraph::new(module.definitions.iter().flatten())
+    }
+
+    #[test]
+    fn test_callers_in_nested_statements() {
+        let call_graph = build_call_graph(
+            "enum shape {
+                circle(radius float)
+                point
+            }
+            func leaf() int { return 1 }
+            func in_if(x int) {
+                if (x > 0) {
+                    loop (x > 1) {
+                        if (x > 2) { } else { println(leaf()) }
+                    }
+                }
+            }
+            func in_match(s shape) {
+                match (s) {
+                    circle(radius) {
+                        for i in 0..3 { var y int = leaf() + i }
+                    }
+                    point { }
+                }
+            }
+            func in_for(xs []int) {
+                for x in xs {
+                    for i in 0..x { for j in leaf()..i { } }
+                }
+            }
+            func unrelated() { println(2) }
+            func main() { in_if(1) }",
+        );
+
+        let callers: Vec<EcoString> = vec!["in_for".into(), "in_if".into(), "in_match".into()];
+        assert_eq!(call_graph.get_callers(&"leaf".into()), callers);
+        assert_eq!(
+            call_graph.get_callers(&"in_if".into()),
+            vec![EcoString::from("main")]
+        );
+        assert!(call_graph.get_callers(&"unrelated".into()).is_empty());
+    }
+}
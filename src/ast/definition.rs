@@ -7,13 +7,18 @@ use vec1::Vec1;
 
 use super::{argument, location::Location, statement};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum DefinitionUntyped {
     Struct {
         location: Location,
         name: EcoString,
         fields: Option<Vec1<StructField>>,
     },
+    Enum {
+        location: Location,
+        name: EcoString,
+        variants: Option<Vec1<EnumVariant>>,
+    },
     Function {
         location: Location,
         name: EcoString,
@@ -29,6 +34,12 @@ pub struct StructField {
     pub type_annotation: UntypedType,
 }
 
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: EcoString,
+    pub fields: Option<Vec1<StructField>>,
+}
+
 #[derive(Debug, Clone)]
 pub enum DefinitionTyped {
     Struct {
@@ -36,6 +47,11 @@ pub enum DefinitionTyped {
         name: EcoString,
         fields: Option<Vec1<StructFieldTyped>>,
     },
+    Enum {
+        location: Location,
+        name: EcoString,
+        variants: Option<Vec1<EnumVariantTyped>>,
+    },
     Function {
         location: Location,
         name: EcoString,
@@ -51,16 +67,22 @@ pub struct StructFieldTyped {
     pub type_: Type,
 }
 
+#[derive(Debug, Clone)]
+pub struct EnumVariantTyped {
+    pub name: EcoString,
+    pub fields: Option<Vec1<StructFieldTyped>>,
+}
+
 impl DefinitionTyped {
     /// Returns the arguments of a definition
     ///
     /// # Errors
     ///
-    /// Returns `ConvertingError` if definition is a struct (only functions have arguments)
+    /// Returns `ConvertingError` if definition is a struct or an enum (only functions have arguments)
     pub fn get_arguments(&self) -> Result<Option<Vec1<argument::ArgumentTyped>>, ConvertingError> {
         match self {
             DefinitionTyped::Function { arguments, .. } => Ok(arguments.clone()),
-            DefinitionTyped::Struct { .. } => Err(ConvertingError {
+            DefinitionTyped::Struct { .. } | DefinitionTyped::Enum { .. } => Err(ConvertingError {
                 error: ConvertingErrorType::UnsupportedType,
                 location: crate::lex::location::Location { start: 0, end: 0 },
             }),
@@ -71,11 +93,11 @@ impl DefinitionTyped {
     ///
     /// # Errors
     ///
-    /// Returns `ConvertingError` if definition is a struct (only functions have return types)
+    /// Returns `ConvertingError` if definition is a struct or an enum (only functions have return types)
     pub fn get_return_type(&self) -> Result<Type, ConvertingError> {
         match self {
             DefinitionTyped::Function { return_type, .. } => Ok(return_type.clone()),
-            DefinitionTyped::Struct { .. } => Err(ConvertingError {
+            DefinitionTyped::Struct { .. } | DefinitionTyped::Enum { .. } => Err(ConvertingError {
                 error: ConvertingErrorType::UnsupportedType,
                 location: crate::lex::location::Location { start: 0, end: 0 },
             }),
@@ -1,5 +1,5 @@
 use crate::ast::location::Location;
-use crate::ast::operator::BinaryOperator;
+use crate::ast::operator::{BinaryOperator, UnaryOperator};
 use crate::type_::{Type, UntypedType};
 use ecow::EcoString;
 use vec1::Vec1;
@@ -28,6 +28,15 @@ pub enum TypedExpression {
         value: char,
         type_: Type,
     },
+    BooleanLiteral {
+        location: Location,
+        value: bool,
+        type_: Type,
+    },
+    NilLiteral {
+        location: Location,
+        type_: Type,
+    },
     VariableValue {
         location: Location,
         name: EcoString,
@@ -61,6 +70,13 @@ pub enum TypedExpression {
         fields: Option<Vec1<StructFieldValueTyped>>,
         type_: Type,
     },
+    EnumInitialization {
+        location: Location,
+        enum_name: EcoString,
+        variant_name: EcoString,
+        arguments: Option<Vec<CallArgumentTyped>>,
+        type_: Type,
+    },
     BinaryOperation {
         location: Location,
         operator: BinaryOperator,
@@ -68,6 +84,12 @@ pub enum TypedExpression {
         right: Box<Self>,
         type_: Type,
     },
+    UnaryOperation {
+        location: Location,
+        operator: UnaryOperator,
+        operand: Box<Self>,
+        type_: Type,
+    },
 }
 
 impl TypedExpression {
@@ -77,6 +99,8 @@ impl TypedExpression {
             TypedExpression::IntLiteral { location, .. }
             | TypedExpression::FloatLiteral { location, .. }
             | TypedExpression::CharLiteral { location, .. }
+            | TypedExpression::BooleanLiteral { location, .. }
+            | TypedExpression::NilLiteral { location, .. }
             | TypedExpression::StringLiteral { location, .. }
             | TypedExpression::VariableValue { location, .. }
             | TypedExpression::FunctionCall { location, .. }
@@ -84,25 +108,32 @@ impl TypedExpression {
             | TypedExpression::ArrayElementAccess { location, .. }
             | TypedExpression::ArrayInitialization { location, .. }
             | TypedExpression::BinaryOperation { location, .. }
-            | TypedExpression::StructInitialization { location, .. } => *location,
+            | TypedExpression::UnaryOperation { location, .. }
+            | TypedExpression::StructInitialization { location, .. }
+            | TypedExpression::EnumInitialization { location, .. } => *location,
         }
     }
 }
 
 impl TypedExpression {
-    #[must_use] pub fn get_type(&self) -> &Type {
+    #[must_use]
+    pub fn get_type(&self) -> &Type {
         match self {
             TypedExpression::IntLiteral { type_, .. }
             | TypedExpression::FloatLiteral { type_, .. }
             | TypedExpression::StringLiteral { type_, .. }
             | TypedExpression::CharLiteral { type_, .. }
+            | TypedExpression::BooleanLiteral { type_, .. }
+            | TypedExpression::NilLiteral { type_, .. }
             | TypedExpression::VariableValue { type_, .. }
             | TypedExpression::FunctionCall { type_, .. }
             | TypedExpression::StructFieldAccess { type_, .. }
             | TypedExpression::ArrayElementAccess { type_, .. }
             | TypedExpression::ArrayInitialization { type_, .. }
             | TypedExpression::StructInitialization { type_, .. }
-            | TypedExpression::BinaryOperation { type_, .. } => type_,
+            | TypedExpression::EnumInitialization { type_, .. }
+            | TypedExpression::BinaryOperation { type_, .. }
+            | TypedExpression::UnaryOperation { type_, .. } => type_,
         }
     }
 }
@@ -165,6 +196,28 @@ impl PartialEq for TypedExpression {
                     type_: t2,
                 },
             ) => l1 == l2 && v1 == v2 && t1 == t2,
+            (
+                TypedExpression::BooleanLiteral {
+                    location: l1,
+                    value: v1,
+                    type_: t1,
+                },
+                TypedExpression::BooleanLiteral {
+                    location: l2,
+                    value: v2,
+                    type_: t2,
+                },
+            ) => l1 == l2 && v1 == v2 && t1 == t2,
+            (
+                TypedExpression::NilLiteral {
+                    location: l1,
+                    type_: t1,
+                },
+                TypedExpression::NilLiteral {
+                    location: l2,
+                    type_: t2,
+                },
+            ) => l1 == l2 && t1 == t2,
             (
                 TypedExpression::VariableValue {
                     location: l1,
@@ -243,6 +296,52 @@ impl PartialEq for TypedExpression {
                     type_: t2,
                 },
             ) => l1 == l2 && f1 == f2 && t1 == t2,
+            (
+                TypedExpression::EnumInitialization {
+                    location: l1,
+                    enum_name: en1,
+                    variant_name: vn1,
+                    arguments: a1,
+                    type_: t1,
+                },
+                TypedExpression::EnumInitialization {
+                    location: l2,
+                    enum_name: en2,
+                    variant_name: vn2,
+                    arguments: a2,
+                    type_: t2,
+                },
+            ) => l1 == l2 && en1 == en2 && vn1 == vn2 && a1 == a2 && t1 == t2,
+            (
+                TypedExpression::BinaryOperation {
+                    location: l1,
+                    operator: o1,
+                    left: le1,
+                    right: r1,
+                    type_: t1,
+                },
+                TypedExpression::BinaryOperation {
+                    location: l2,
+                    operator: o2,
+                    left: le2,
+                    right: r2,
+                    type_: t2,
+                },
+            ) => l1 == l2 && o1 == o2 && le1 == le2 && r1 == r2 && t1 == t2,
+            (
+                TypedExpression::UnaryOperation {
+                    location: l1,
+                    operator: o1,
+                    operand: op1,
+                    type_: t1,
+                },
+                TypedExpression::UnaryOperation {
+                    location: l2,
+                    operator: o2,
+                    operand: op2,
+                    type_: t2,
+                },
+            ) => l1 == l2 && o1 == o2 && op1 == op2 && t1 == t2,
             _ => false,
         }
     }
@@ -268,6 +367,13 @@ pub enum UntypedExpression {
         location: Location,
         value: EcoString,
     },
+    BooleanLiteral {
+        location: Location,
+        value: bool,
+    },
+    NilLiteral {
+        location: Location,
+    },
     VariableValue {
         location: Location,
         name: EcoString,
@@ -278,6 +384,11 @@ pub enum UntypedExpression {
         left: Box<Self>,
         right: Box<Self>,
     },
+    UnaryOperation {
+        location: Location,
+        operator: UnaryOperator,
+        operand: Box<Self>,
+    },
     FunctionCall {
         location: Location,
         function_name: EcoString,
@@ -303,6 +414,12 @@ pub enum UntypedExpression {
         type_annotation: UntypedType,
         fields: Option<Vec1<StructFieldValue>>,
     },
+    EnumInitialization {
+        location: Location,
+        enum_name: EcoString,
+        variant_name: EcoString,
+        arguments: Option<Vec1<CallArgumentUntyped>>,
+    },
 }
 
 impl UntypedExpression {
@@ -312,14 +429,18 @@ impl UntypedExpression {
             UntypedExpression::IntLiteral { location, .. }
             | UntypedExpression::FloatLiteral { location, .. }
             | UntypedExpression::CharLiteral { location, .. }
+            | UntypedExpression::BooleanLiteral { location, .. }
+            | UntypedExpression::NilLiteral { location }
             | UntypedExpression::StringLiteral { location, .. }
             | UntypedExpression::VariableValue { location, .. }
             | UntypedExpression::BinaryOperation { location, .. }
+            | UntypedExpression::UnaryOperation { location, .. }
             | UntypedExpression::FunctionCall { location, .. }
             | UntypedExpression::StructFieldAccess { location, .. }
             | UntypedExpression::ArrayElementAccess { location, .. }
             | UntypedExpression::ArrayInitialization { location, .. }
-            | UntypedExpression::StructInitialization { location, .. } => *location,
+            | UntypedExpression::StructInitialization { location, .. }
+            | UntypedExpression::EnumInitialization { location, .. } => *location,
         }
     }
 }
@@ -0,0 +1,61 @@
+use crate::{ast::location::Location, type_::Type};
+
+use super::expression::{TypedExpression, UntypedExpression};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UntypedIterable {
+    Range {
+        location: Location,
+        start: Box<UntypedExpression>,
+        end: Box<UntypedExpression>,
+    },
+    Array {
+        location: Location,
+        array: Box<UntypedExpression>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypedIterable {
+    Range {
+        location: Location,
+        start: Box<TypedExpression>,
+        end: Box<TypedExpression>,
+    },
+    Array {
+        location: Location,
+        array: Box<TypedExpression>,
+        element_type: Type,
+    },
+}
+
+impl UntypedIterable {
+    #[must_use]
+    pub fn get_location(&self) -> Location {
+        match self {
+            UntypedIterable::Range { location, .. } | UntypedIterable::Array { location, .. } => {
+                *location
+            }
+        }
+    }
+}
+
+impl TypedIterable {
+    #[must_use]
+    pub fn get_location(&self) -> Location {
+        match self {
+            TypedIterable::Range { location, .. } | TypedIterable::Array { location, .. } => {
+                *location
+            }
+        }
+    }
+
+    /// Type of the value loop variable is bound to on each iteration
+    #[must_use]
+    pub fn get_element_type(&self) -> Type {
+        match self {
+            TypedIterable::Range { .. } => Type::Int,
+            TypedIterable::Array { element_type, .. } => element_type.clone(),
+        }
+    }
+}
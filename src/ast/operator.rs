@@ -22,6 +22,11 @@ pub enum BinaryOperator {
     DivisionFloat,
     Modulo,
     Concatenation,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
 impl BinaryOperator {
@@ -40,15 +45,26 @@ impl BinaryOperator {
             | BinaryOperator::GreaterEqualFloat
             | BinaryOperator::GreaterFloat => 4,
             BinaryOperator::Concatenation => 5,
+            BinaryOperator::BitwiseOr => 6,
+            BinaryOperator::BitwiseXor => 7,
+            BinaryOperator::BitwiseAnd => 8,
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => 9,
             BinaryOperator::AdditionInt
             | BinaryOperator::AdditionFloat
             | BinaryOperator::SubtractionInt
-            | BinaryOperator::SubtractionFloat => 6,
+            | BinaryOperator::SubtractionFloat => 10,
             BinaryOperator::MultipicationInt
             | BinaryOperator::MultipicationFloat
             | BinaryOperator::DivisionInt
             | BinaryOperator::DivisionFloat
-            | BinaryOperator::Modulo => 7,
+            | BinaryOperator::Modulo => 11,
         }
     }
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnaryOperator {
+    Not,
+    NegationInt,
+    NegationFloat,
+}
@@ -0,0 +1,63 @@
+use crate::{ast::location::Location, type_::Type};
+use ecow::EcoString;
+use vec1::Vec1;
+
+use super::statement::{TypedStatement, UntypedStatement};
+
+/// Name, that matches anything and binds nothing
+pub const WILDCARD: &str = "_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UntypedPattern {
+    Variant {
+        location: Location,
+        variant_name: EcoString,
+        bindings: Option<Vec1<EcoString>>,
+    },
+    Wildcard {
+        location: Location,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypedPattern {
+    Variant {
+        location: Location,
+        variant_name: EcoString,
+        bindings: Option<Vec1<PatternBinding>>,
+    },
+    Wildcard {
+        location: Location,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternBinding {
+    pub name: EcoString,
+    pub type_: Type,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UntypedMatchArm {
+    pub location: Location,
+    pub pattern: UntypedPattern,
+    pub body: Option<Vec1<UntypedStatement>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedMatchArm {
+    pub location: Location,
+    pub pattern: TypedPattern,
+    pub body: Option<Vec1<TypedStatement>>,
+}
+
+impl UntypedPattern {
+    #[must_use]
+    pub fn get_location(&self) -> Location {
+        match self {
+            UntypedPattern::Variant { location, .. } | UntypedPattern::Wildcard { location } => {
+                *location
+            }
+        }
+    }
+}
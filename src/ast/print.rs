@@ -1,7 +1,10 @@
 use crate::ast::expression::UntypedExpression;
+use crate::ast::iterable::UntypedIterable;
 use crate::ast::module::Module;
+use crate::ast::pattern::{UntypedPattern, WILDCARD};
 use crate::ast::statement::UntypedStatement;
 use crate::ast::{argument, definition, statement};
+use ecow::EcoString;
 use std::fmt;
 
 use super::reassignment::UntypedReassignmentTarget;
@@ -89,6 +92,50 @@ fn print_definition(
                 }
             }
         }
+        definition::DefinitionUntyped::Enum {
+            location,
+            name,
+            variants,
+        } => {
+            writeln!(
+                formatter,
+                "{}Enum {} ({}..{})",
+                make_prefix(indentation_levels),
+                name,
+                location.start,
+                location.end
+            )?;
+
+            if let Some(variants) = variants {
+                let mut new_indentation_levels = indentation_levels.to_vec();
+                new_indentation_levels.push(false);
+
+                for (i, variant) in variants.iter().enumerate() {
+                    let mut variant_levels = new_indentation_levels.clone();
+                    variant_levels.push(i < variants.len() - 1);
+                    writeln!(
+                        formatter,
+                        "{}Variant: {}",
+                        make_prefix(&variant_levels),
+                        variant.name
+                    )?;
+
+                    if let Some(fields) = &variant.fields {
+                        for (j, field) in fields.iter().enumerate() {
+                            let mut field_levels = variant_levels.clone();
+                            field_levels.push(j < fields.len() - 1);
+                            writeln!(
+                                formatter,
+                                "{}Field: {} - {:?}",
+                                make_prefix(&field_levels),
+                                field.name,
+                                field.type_annotation
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
         definition::DefinitionUntyped::Function {
             location,
             name,
@@ -312,7 +359,11 @@ fn print_statement(
                 formatter,
             )?;
         }
-        UntypedStatement::Loop { body, location } => {
+        UntypedStatement::Loop {
+            condition,
+            body,
+            location,
+        } => {
             writeln!(
                 formatter,
                 "{}Loop ({}..{})",
@@ -321,10 +372,81 @@ fn print_statement(
                 location.end
             )?;
 
+            let mut new_indentation_levels = indentation_levels.to_vec();
+
+            if let Some(condition) = condition {
+                new_indentation_levels.push(body.is_some());
+                writeln!(
+                    formatter,
+                    "{}Condition:",
+                    make_prefix(&new_indentation_levels)
+                )?;
+
+                let mut condition_levels = new_indentation_levels.clone();
+                condition_levels.push(false);
+
+                print_expression(condition, &condition_levels, formatter)?;
+                new_indentation_levels.pop();
+            }
+
             if let Some(statements) = body {
-                let mut new_indentation_levels = indentation_levels.to_vec();
                 new_indentation_levels.push(false);
 
+                if condition.is_some() {
+                    writeln!(formatter, "{}Body:", make_prefix(&new_indentation_levels))?;
+                }
+
+                for (i, statement) in statements.iter().enumerate() {
+                    let mut statement_levels = new_indentation_levels.clone();
+                    statement_levels.push(i < statements.len() - 1);
+                    print_statement(statement, &statement_levels, formatter)?;
+                }
+            }
+        }
+        UntypedStatement::For {
+            variable_name,
+            iterable,
+            body,
+            location,
+        } => {
+            writeln!(
+                formatter,
+                "{}For {} ({}..{})",
+                make_prefix(indentation_levels),
+                variable_name,
+                location.start,
+                location.end
+            )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+
+            let iterable_parts = match iterable {
+                UntypedIterable::Range { start, end, .. } => {
+                    vec![("Range start:", start), ("Range end:", end)]
+                }
+                UntypedIterable::Array { array, .. } => vec![("Array:", array)],
+            };
+
+            for (i, (title, expression)) in iterable_parts.iter().enumerate() {
+                new_indentation_levels.push(i < iterable_parts.len() - 1 || body.is_some());
+                writeln!(
+                    formatter,
+                    "{}{}",
+                    make_prefix(&new_indentation_levels),
+                    title
+                )?;
+
+                let mut expression_levels = new_indentation_levels.clone();
+                expression_levels.push(false);
+
+                print_expression(expression, &expression_levels, formatter)?;
+                new_indentation_levels.pop();
+            }
+
+            if let Some(statements) = body {
+                new_indentation_levels.push(false);
+                writeln!(formatter, "{}Body:", make_prefix(&new_indentation_levels))?;
+
                 for (i, statement) in statements.iter().enumerate() {
                     let mut statement_levels = new_indentation_levels.clone();
                     statement_levels.push(i < statements.len() - 1);
@@ -398,6 +520,55 @@ fn print_statement(
                 }
             }
         }
+        UntypedStatement::Match {
+            value,
+            arms,
+            location,
+        } => {
+            writeln!(
+                formatter,
+                "{}Match ({}..{})",
+                make_prefix(indentation_levels),
+                location.start,
+                location.end
+            )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+            new_indentation_levels.push(arms.is_some());
+
+            writeln!(formatter, "{}Value:", make_prefix(&new_indentation_levels))?;
+
+            let mut value_levels = new_indentation_levels.clone();
+            value_levels.push(false);
+
+            print_expression(value, &value_levels, formatter)?;
+            new_indentation_levels.pop();
+
+            if let Some(arms) = arms {
+                for (i, arm) in arms.iter().enumerate() {
+                    let mut arm_levels = new_indentation_levels.clone();
+                    arm_levels.push(i < arms.len() - 1);
+
+                    writeln!(
+                        formatter,
+                        "{}Arm {} ({}..{})",
+                        make_prefix(&arm_levels),
+                        arm.pattern,
+                        arm.location.start,
+                        arm.location.end
+                    )?;
+
+                    if let Some(statements) = &arm.body {
+                        for (j, statement) in statements.iter().enumerate() {
+                            let mut statement_levels = arm_levels.clone();
+                            statement_levels.push(j < statements.len() - 1);
+
+                            print_statement(statement, &statement_levels, formatter)?;
+                        }
+                    }
+                }
+            }
+        }
         UntypedStatement::Break { location } => {
             writeln!(
                 formatter,
@@ -407,6 +578,15 @@ fn print_statement(
                 location.end
             )?;
         }
+        UntypedStatement::Continue { location } => {
+            writeln!(
+                formatter,
+                "{}Continue ({}..{})",
+                make_prefix(indentation_levels),
+                location.start,
+                location.end
+            )?;
+        }
         UntypedStatement::Return { location, value } => {
             writeln!(
                 formatter,
@@ -431,7 +611,7 @@ fn print_statement(
                 location.end
             )?;
         }
-        UntypedStatement::Panic { location } => {
+        UntypedStatement::Panic { location, message } => {
             writeln!(
                 formatter,
                 "{}Panic ({}..{})",
@@ -439,8 +619,12 @@ fn print_statement(
                 location.start,
                 location.end
             )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+            new_indentation_levels.push(false);
+            print_expression(message, &new_indentation_levels, formatter)?;
         }
-        UntypedStatement::Exit { location } => {
+        UntypedStatement::Exit { location, code } => {
             writeln!(
                 formatter,
                 "{}Exit ({}..{})",
@@ -448,6 +632,10 @@ fn print_statement(
                 location.start,
                 location.end
             )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+            new_indentation_levels.push(false);
+            print_expression(code, &new_indentation_levels, formatter)?;
         }
     }
 
@@ -500,6 +688,25 @@ fn print_expression(
                 location.end
             )?;
         }
+        UntypedExpression::BooleanLiteral { location, value } => {
+            writeln!(
+                formatter,
+                "{}Boolean: {} ({}..{})",
+                make_prefix(indentation_levels),
+                value,
+                location.start,
+                location.end
+            )?;
+        }
+        UntypedExpression::NilLiteral { location } => {
+            writeln!(
+                formatter,
+                "{}Nil ({}..{})",
+                make_prefix(indentation_levels),
+                location.start,
+                location.end
+            )?;
+        }
         UntypedExpression::VariableValue { location, name } => {
             writeln!(
                 formatter,
@@ -552,6 +759,34 @@ fn print_expression(
 
             print_expression(right, &right_levels, formatter)?;
         }
+        UntypedExpression::UnaryOperation {
+            location,
+            operator,
+            operand,
+        } => {
+            writeln!(
+                formatter,
+                "{}Unary operation: {:?} ({}..{})",
+                make_prefix(indentation_levels),
+                operator,
+                location.start,
+                location.end
+            )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+
+            new_indentation_levels.push(false);
+            writeln!(
+                formatter,
+                "{}Operand:",
+                make_prefix(&new_indentation_levels)
+            )?;
+
+            let mut operand_levels = new_indentation_levels.clone();
+            operand_levels.push(false);
+
+            print_expression(operand, &operand_levels, formatter)?;
+        }
         UntypedExpression::FunctionCall {
             location,
             function_name,
@@ -586,6 +821,42 @@ fn print_expression(
                 }
             }
         }
+        UntypedExpression::EnumInitialization {
+            location,
+            enum_name,
+            variant_name,
+            arguments,
+        } => {
+            writeln!(
+                formatter,
+                "{}Enum initialization: {}::{} ({}..{})",
+                make_prefix(indentation_levels),
+                enum_name,
+                variant_name,
+                location.start,
+                location.end
+            )?;
+            if let Some(arguments) = arguments {
+                let mut new_indentation_levels = indentation_levels.to_vec();
+
+                for (i, argument) in arguments.iter().enumerate() {
+                    new_indentation_levels.push(i < arguments.len() - 1);
+                    writeln!(
+                        formatter,
+                        "{}Argument ({}..{}):",
+                        make_prefix(&new_indentation_levels),
+                        argument.location.start,
+                        argument.location.end
+                    )?;
+
+                    let mut argument_levels = new_indentation_levels.clone();
+                    argument_levels.push(false);
+
+                    print_expression(&argument.value, &argument_levels, formatter)?;
+                    new_indentation_levels.pop();
+                }
+            }
+        }
         UntypedExpression::StructFieldAccess {
             location,
             struct_name,
@@ -696,3 +967,29 @@ fn print_expression(
 
     Ok(())
 }
+
+impl fmt::Display for UntypedPattern {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UntypedPattern::Variant {
+                variant_name,
+                bindings,
+                ..
+            } => {
+                write!(formatter, "{variant_name}")?;
+
+                if let Some(bindings) = bindings {
+                    let bindings = bindings
+                        .iter()
+                        .map(EcoString::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(formatter, "({bindings})")?;
+                }
+
+                Ok(())
+            }
+            UntypedPattern::Wildcard { .. } => write!(formatter, "{WILDCARD}"),
+        }
+    }
+}
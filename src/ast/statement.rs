@@ -1,9 +1,12 @@
 use super::{
     assignment::{TypedAssignment, UntypedAssignment},
+    iterable::{TypedIterable, UntypedIterable},
     location::Location,
+    pattern::{TypedMatchArm, UntypedMatchArm},
     reassignment::{TypedReassignment, UntypedReassignment},
 };
 use crate::ast::expression::{TypedExpression, UntypedExpression};
+use ecow::EcoString;
 use vec1::Vec1;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,6 +15,13 @@ pub enum TypedStatement {
     Assignment(TypedAssignment),
     Reassignment(TypedReassignment),
     Loop {
+        condition: Option<Box<TypedExpression>>,
+        body: Option<Vec1<Self>>,
+        location: Location,
+    },
+    For {
+        variable_name: EcoString,
+        iterable: TypedIterable,
         body: Option<Vec1<Self>>,
         location: Location,
     },
@@ -21,9 +31,17 @@ pub enum TypedStatement {
         else_body: Option<Vec1<Self>>,
         location: Location,
     },
+    Match {
+        value: Box<TypedExpression>,
+        arms: Option<Vec1<TypedMatchArm>>,
+        location: Location,
+    },
     Break {
         location: Location,
     },
+    Continue {
+        location: Location,
+    },
     Return {
         location: Location,
         value: Option<Box<TypedExpression>>,
@@ -33,9 +51,11 @@ pub enum TypedStatement {
     },
     Panic {
         location: Location,
+        message: Box<TypedExpression>,
     },
     Exit {
         location: Location,
+        code: Box<TypedExpression>,
     },
 }
 
@@ -45,6 +65,13 @@ pub enum UntypedStatement {
     Assignment(UntypedAssignment),
     Reassignment(UntypedReassignment),
     Loop {
+        condition: Option<Box<UntypedExpression>>,
+        body: Option<Vec1<Self>>,
+        location: Location,
+    },
+    For {
+        variable_name: EcoString,
+        iterable: UntypedIterable,
         body: Option<Vec1<Self>>,
         location: Location,
     },
@@ -54,9 +81,17 @@ pub enum UntypedStatement {
         else_body: Option<Vec1<Self>>,
         location: Location,
     },
+    Match {
+        value: Box<UntypedExpression>,
+        arms: Option<Vec1<UntypedMatchArm>>,
+        location: Location,
+    },
     Break {
         location: Location,
     },
+    Continue {
+        location: Location,
+    },
     Return {
         location: Location,
         value: Option<Box<UntypedExpression>>,
@@ -66,9 +101,11 @@ pub enum UntypedStatement {
     },
     Panic {
         location: Location,
+        message: Box<UntypedExpression>,
     },
     Exit {
         location: Location,
+        code: Box<UntypedExpression>,
     },
 }
 
@@ -80,12 +117,15 @@ impl UntypedStatement {
             UntypedStatement::Assignment(assignment) => assignment.location,
             UntypedStatement::Reassignment(reassignment) => reassignment.location,
             UntypedStatement::Loop { location, .. }
+            | UntypedStatement::For { location, .. }
             | UntypedStatement::If { location, .. }
+            | UntypedStatement::Match { location, .. }
             | UntypedStatement::Return { location, .. }
             | UntypedStatement::Todo { location, .. }
             | UntypedStatement::Panic { location, .. }
             | UntypedStatement::Exit { location, .. }
-            | UntypedStatement::Break { location, .. } => *location,
+            | UntypedStatement::Break { location, .. }
+            | UntypedStatement::Continue { location, .. } => *location,
         }
     }
 }
@@ -98,12 +138,15 @@ impl TypedStatement {
             TypedStatement::Assignment(assignment) => assignment.location,
             TypedStatement::Reassignment(reassignment) => reassignment.location,
             TypedStatement::Loop { location, .. }
+            | TypedStatement::For { location, .. }
             | TypedStatement::If { location, .. }
+            | TypedStatement::Match { location, .. }
             | TypedStatement::Return { location, .. }
             | TypedStatement::Todo { location, .. }
             | TypedStatement::Panic { location, .. }
             | TypedStatement::Exit { location, .. }
-            | TypedStatement::Break { location, .. } => *location,
+            | TypedStatement::Break { location, .. }
+            | TypedStatement::Continue { location, .. } => *location,
         }
     }
 }
@@ -1,5 +1,7 @@
 use crate::ast::expression::TypedExpression;
+use crate::ast::iterable::TypedIterable;
 use crate::ast::module::Module;
+use crate::ast::pattern::{TypedPattern, WILDCARD};
 use crate::ast::reassignment::TypedReassignmentTarget;
 use crate::ast::statement::TypedStatement;
 use crate::ast::{argument, definition, statement};
@@ -34,6 +36,32 @@ impl fmt::Display for Type {
     }
 }
 
+impl fmt::Display for TypedPattern {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedPattern::Variant {
+                variant_name,
+                bindings,
+                ..
+            } => {
+                write!(formatter, "{variant_name}")?;
+
+                if let Some(bindings) = bindings {
+                    let bindings = bindings
+                        .iter()
+                        .map(|binding| format!("{} {}", binding.name, binding.type_))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(formatter, "({bindings})")?;
+                }
+
+                Ok(())
+            }
+            TypedPattern::Wildcard { .. } => write!(formatter, "{WILDCARD}"),
+        }
+    }
+}
+
 fn print_expression(
     expr: &TypedExpression,
     indentation_levels: &[bool],
@@ -100,6 +128,31 @@ fn print_expression(
                 type_
             )?;
         }
+        TypedExpression::BooleanLiteral {
+            location,
+            value,
+            type_,
+        } => {
+            writeln!(
+                formatter,
+                "{}Boolean: {} ({}..{}) type: {}",
+                make_prefix(indentation_levels),
+                value,
+                location.start,
+                location.end,
+                type_
+            )?;
+        }
+        TypedExpression::NilLiteral { location, type_ } => {
+            writeln!(
+                formatter,
+                "{}Nil ({}..{}) type: {}",
+                make_prefix(indentation_levels),
+                location.start,
+                location.end,
+                type_
+            )?;
+        }
         TypedExpression::VariableValue {
             location,
             name,
@@ -159,6 +212,36 @@ fn print_expression(
 
             print_expression(right, &right_levels, formatter)?;
         }
+        TypedExpression::UnaryOperation {
+            location,
+            operator,
+            operand,
+            type_,
+        } => {
+            writeln!(
+                formatter,
+                "{}Unary operation: {:?} ({}..{}) type: {}",
+                make_prefix(indentation_levels),
+                operator,
+                location.start,
+                location.end,
+                type_
+            )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+
+            new_indentation_levels.push(false);
+            writeln!(
+                formatter,
+                "{}Operand:",
+                make_prefix(&new_indentation_levels)
+            )?;
+
+            let mut operand_levels = new_indentation_levels.clone();
+            operand_levels.push(false);
+
+            print_expression(operand, &operand_levels, formatter)?;
+        }
         TypedExpression::FunctionCall {
             location,
             function_name,
@@ -196,6 +279,45 @@ fn print_expression(
                 }
             }
         }
+        TypedExpression::EnumInitialization {
+            location,
+            enum_name,
+            variant_name,
+            arguments,
+            type_,
+        } => {
+            writeln!(
+                formatter,
+                "{}Enum initialization: {}::{} ({}..{}) type: {}",
+                make_prefix(indentation_levels),
+                enum_name,
+                variant_name,
+                location.start,
+                location.end,
+                type_
+            )?;
+            if let Some(arguments) = arguments {
+                let mut new_indentation_levels = indentation_levels.to_vec();
+
+                for (i, argument) in arguments.iter().enumerate() {
+                    new_indentation_levels.push(i < arguments.len() - 1);
+                    writeln!(
+                        formatter,
+                        "{}Argument ({}..{}): type: {}",
+                        make_prefix(&new_indentation_levels),
+                        argument.location.start,
+                        argument.location.end,
+                        argument.type_,
+                    )?;
+
+                    let mut argument_levels = new_indentation_levels.clone();
+                    argument_levels.push(false);
+
+                    print_expression(&argument.value, &argument_levels, formatter)?;
+                    new_indentation_levels.pop();
+                }
+            }
+        }
         TypedExpression::StructFieldAccess {
             location,
             struct_name,
@@ -469,7 +591,11 @@ fn print_statement(
                 formatter,
             )?;
         }
-        TypedStatement::Loop { body, location } => {
+        TypedStatement::Loop {
+            condition,
+            body,
+            location,
+        } => {
             writeln!(
                 formatter,
                 "{}Loop ({}..{})",
@@ -478,10 +604,82 @@ fn print_statement(
                 location.end
             )?;
 
+            let mut new_indentation_levels = indentation_levels.to_vec();
+
+            if let Some(condition) = condition {
+                new_indentation_levels.push(body.is_some());
+                writeln!(
+                    formatter,
+                    "{}Condition:",
+                    make_prefix(&new_indentation_levels)
+                )?;
+
+                let mut condition_levels = new_indentation_levels.clone();
+                condition_levels.push(false);
+
+                print_expression(condition, &condition_levels, formatter)?;
+                new_indentation_levels.pop();
+            }
+
             if let Some(statements) = body {
-                let mut new_indentation_levels = indentation_levels.to_vec();
                 new_indentation_levels.push(false);
 
+                if condition.is_some() {
+                    writeln!(formatter, "{}Body:", make_prefix(&new_indentation_levels))?;
+                }
+
+                for (i, statement) in statements.iter().enumerate() {
+                    let mut statement_levels = new_indentation_levels.clone();
+                    statement_levels.push(i < statements.len() - 1);
+                    print_statement(statement, &statement_levels, formatter)?;
+                }
+            }
+        }
+        TypedStatement::For {
+            variable_name,
+            iterable,
+            body,
+            location,
+        } => {
+            writeln!(
+                formatter,
+                "{}For {} ({}..{}) type: {}",
+                make_prefix(indentation_levels),
+                variable_name,
+                location.start,
+                location.end,
+                iterable.get_element_type()
+            )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+
+            let iterable_parts = match iterable {
+                TypedIterable::Range { start, end, .. } => {
+                    vec![("Range start:", start), ("Range end:", end)]
+                }
+                TypedIterable::Array { array, .. } => vec![("Array:", array)],
+            };
+
+            for (i, (title, expression)) in iterable_parts.iter().enumerate() {
+                new_indentation_levels.push(i < iterable_parts.len() - 1 || body.is_some());
+                writeln!(
+                    formatter,
+                    "{}{}",
+                    make_prefix(&new_indentation_levels),
+                    title
+                )?;
+
+                let mut expression_levels = new_indentation_levels.clone();
+                expression_levels.push(false);
+
+                print_expression(expression, &expression_levels, formatter)?;
+                new_indentation_levels.pop();
+            }
+
+            if let Some(statements) = body {
+                new_indentation_levels.push(false);
+                writeln!(formatter, "{}Body:", make_prefix(&new_indentation_levels))?;
+
                 for (i, statement) in statements.iter().enumerate() {
                     let mut statement_levels = new_indentation_levels.clone();
                     statement_levels.push(i < statements.len() - 1);
@@ -555,6 +753,55 @@ fn print_statement(
                 }
             }
         }
+        TypedStatement::Match {
+            value,
+            arms,
+            location,
+        } => {
+            writeln!(
+                formatter,
+                "{}Match ({}..{})",
+                make_prefix(indentation_levels),
+                location.start,
+                location.end
+            )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+            new_indentation_levels.push(arms.is_some());
+
+            writeln!(formatter, "{}Value:", make_prefix(&new_indentation_levels))?;
+
+            let mut value_levels = new_indentation_levels.clone();
+            value_levels.push(false);
+
+            print_expression(value, &value_levels, formatter)?;
+            new_indentation_levels.pop();
+
+            if let Some(arms) = arms {
+                for (i, arm) in arms.iter().enumerate() {
+                    let mut arm_levels = new_indentation_levels.clone();
+                    arm_levels.push(i < arms.len() - 1);
+
+                    writeln!(
+                        formatter,
+                        "{}Arm {} ({}..{})",
+                        make_prefix(&arm_levels),
+                        arm.pattern,
+                        arm.location.start,
+                        arm.location.end
+                    )?;
+
+                    if let Some(statements) = &arm.body {
+                        for (j, statement) in statements.iter().enumerate() {
+                            let mut statement_levels = arm_levels.clone();
+                            statement_levels.push(j < statements.len() - 1);
+
+                            print_statement(statement, &statement_levels, formatter)?;
+                        }
+                    }
+                }
+            }
+        }
         TypedStatement::Break { location } => {
             writeln!(
                 formatter,
@@ -564,6 +811,15 @@ fn print_statement(
                 location.end
             )?;
         }
+        TypedStatement::Continue { location } => {
+            writeln!(
+                formatter,
+                "{}Continue ({}..{})",
+                make_prefix(indentation_levels),
+                location.start,
+                location.end
+            )?;
+        }
         TypedStatement::Return { location, value } => {
             writeln!(
                 formatter,
@@ -588,7 +844,7 @@ fn print_statement(
                 location.end
             )?;
         }
-        TypedStatement::Panic { location } => {
+        TypedStatement::Panic { location, message } => {
             writeln!(
                 formatter,
                 "{}Panic ({}..{})",
@@ -596,8 +852,12 @@ fn print_statement(
                 location.start,
                 location.end
             )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+            new_indentation_levels.push(false);
+            print_expression(message, &new_indentation_levels, formatter)?;
         }
-        TypedStatement::Exit { location } => {
+        TypedStatement::Exit { location, code } => {
             writeln!(
                 formatter,
                 "{}Exit ({}..{})",
@@ -605,6 +865,10 @@ fn print_statement(
                 location.start,
                 location.end
             )?;
+
+            let mut new_indentation_levels = indentation_levels.to_vec();
+            new_indentation_levels.push(false);
+            print_expression(code, &new_indentation_levels, formatter)?;
         }
     }
 
@@ -675,6 +939,50 @@ fn print_definition(
                 }
             }
         }
+        definition::DefinitionTyped::Enum {
+            location,
+            name,
+            variants,
+        } => {
+            writeln!(
+                formatter,
+                "{}Enum {} ({}..{})",
+                make_prefix(indentation_levels),
+                name,
+                location.start,
+                location.end
+            )?;
+
+            if let Some(variants) = variants {
+                let mut new_indentation_levels = indentation_levels.to_vec();
+                new_indentation_levels.push(false);
+
+                for (i, variant) in variants.iter().enumerate() {
+                    let mut variant_levels = new_indentation_levels.clone();
+                    variant_levels.push(i < variants.len() - 1);
+                    writeln!(
+                        formatter,
+                        "{}Variant: {}",
+                        make_prefix(&variant_levels),
+                        variant.name
+                    )?;
+
+                    if let Some(fields) = &variant.fields {
+                        for (j, field) in fields.iter().enumerate() {
+                            let mut field_levels = variant_levels.clone();
+                            field_levels.push(j < fields.len() - 1);
+                            writeln!(
+                                formatter,
+                                "{}Field: {} - {:?}",
+                                make_prefix(&field_levels),
+                                field.name,
+                                field.type_
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
         definition::DefinitionTyped::Function {
             location,
             name,
@@ -32,14 +32,28 @@ pub fn get_user_menu_decision() -> MenuAction {
     decision
 }
 
-/// Will prompt user with editor to get raw text input
+/// Will prompt user with editor to get raw text input,
+/// editor is filled with `predefined_text`, if one is given
 ///
 /// # Panics
 ///
 /// Will panic if failed to prompt
 #[must_use]
-pub fn get_user_input() -> String {
-    let input = inquire::Editor::new("Input:").prompt().unwrap();
+pub fn get_user_input(predefined_text: Option<&str>) -> String {
+    let mut editor = inquire::Editor::new("Input:");
+    if let Some(predefined_text) = predefined_text {
+        editor = editor.with_predefined_text(predefined_text);
+    }
+
+    editor.prompt().unwrap()
+}
 
-    input
+/// Will prompt user with selection of awa call stack frames, getting index
+/// of the frame to restart. The innermost frame is the first one, and it is
+/// chosen, if prompt fails
+#[must_use]
+pub fn get_user_frame_decision(frames: Vec<String>) -> usize {
+    inquire::Select::new("Select the call to restart:", frames)
+        .raw_prompt()
+        .map_or(0, |option| option.index)
 }
@@ -15,7 +15,7 @@ fn print_wrapped_panic(info: &PanicHookInfo<'_>) {
         info.payload().downcast_ref::<String>(),
     ) {
         (Some(message), _) => (*message).to_string(),
-        (_, Some(message)) => message.to_string(),
+        (_, Some(message)) => message.clone(),
         (None, None) => "unknown error".into(),
     };
 
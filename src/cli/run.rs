@@ -1,15 +1,32 @@
 use camino::Utf8PathBuf;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::{
+    io::{IsTerminal, Write},
+    sync::mpsc::{channel, Receiver, Sender},
+};
 use termion::input::TermRead;
 
-use crate::driver::{self, BackwardsCommunication, Command};
+use crate::{
+    ast::{analyzer::TypeAnalyzer, module},
+    driver::{self, BackwardsCommunication, Command, RunOptions},
+};
 
 /// Handle run cli command - read from provided filename and run VM with interpreted bytecode
 ///
+/// Runs straight through, without menu, if `batch` is set or
+/// stdin is not a terminal
+///
+/// Returns exit status of the awa program: `0` on successful finish, the code passed
+/// to `exit(code)`, or `driver::PANIC_EXIT_CODE` on `panic`. Status `1` means, that
+/// the program could not be read or compiled
+///
+/// Code of `exit` must be in `0..=255`, as the OS keeps only its low byte. Other
+/// codes fail at runtime instead of being truncated
+///
 /// # Panics
 ///
-/// Will panic if file does not exist, or in case of unexpected internal errors
-pub fn handle(filename: Option<Utf8PathBuf>, unoptimized: bool) {
+/// Will panic in case of unexpected internal errors
+#[must_use]
+pub fn handle(filename: Option<Utf8PathBuf>, options: RunOptions, batch: bool) -> i32 {
     let filename = match filename {
         Some(filename) => filename,
         None => "main.awa".into(),
@@ -20,14 +37,34 @@ pub fn handle(filename: Option<Utf8PathBuf>, unoptimized: bool) {
         Ok(input) => input,
         Err(err) => {
             println!("{err}");
-            return;
+            return 1;
         }
     };
 
-    let Some((mut analyzer, module)) = driver::build_ast(filename, &input) else {
-        return;
+    let Some((mut analyzer, module)) = driver::build_ast(filename.clone(), &input) else {
+        return 1;
+    };
+
+    let code = if batch || !std::io::stdin().is_terminal() {
+        driver::run_batch(&filename, &input.into(), &mut analyzer, &module, options)
+    } else {
+        run_interactive(filename, input, analyzer, module, options)
     };
 
+    // NOTE: output is not flushed on `std::process::exit`
+    let _ = std::io::stdout().flush();
+
+    code
+}
+
+/// Run VM in the driver thread, opening menu on key press
+fn run_interactive(
+    filename: Utf8PathBuf,
+    input: String,
+    mut analyzer: TypeAnalyzer,
+    module: module::Typed,
+    options: RunOptions,
+) -> i32 {
     let (driver_sender, driver_reciever): (Sender<Command>, Receiver<Command>) = channel();
     let (driver_backwards_sender, driver_backwards_reciever): (
         Sender<BackwardsCommunication>,
@@ -36,9 +73,11 @@ pub fn handle(filename: Option<Utf8PathBuf>, unoptimized: bool) {
 
     let _ = std::thread::spawn(move || {
         driver::run(
+            &filename,
+            &input.into(),
             &mut analyzer,
             &module,
-            unoptimized,
+            options,
             &driver_reciever,
             &driver_backwards_sender,
         );
@@ -77,7 +116,7 @@ pub fn handle(filename: Option<Utf8PathBuf>, unoptimized: bool) {
                 BackwardsCommunication::RequireHotswap => {
                     require_hotswap = true;
                 }
-                BackwardsCommunication::Finished => return,
+                BackwardsCommunication::Finished(code) => return code,
             }
         }
 
@@ -95,7 +134,7 @@ pub fn handle(filename: Option<Utf8PathBuf>, unoptimized: bool) {
                 BackwardsCommunication::RequireHotswap => {
                     require_hotswap = true;
                 }
-                BackwardsCommunication::Finished => return,
+                BackwardsCommunication::Finished(code) => return code,
                 BackwardsCommunication::ReturnedToExecution => {}
             }
 
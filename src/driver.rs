@@ -1,47 +1,142 @@
+use std::{collections::HashMap, time::Duration};
+
 use camino::Utf8PathBuf;
+use ecow::EcoString;
+use vec1::Vec1;
 
 use crate::{
     ast::{
-        analyzer::TypeAnalyzer,
+        analyzer::{Hotswap, TypeAnalyzer},
         definition::DefinitionTyped,
         module::{self, Module},
     },
     cli::{self, input::MenuAction},
+    diagnostic::{self, Diagnostic},
     error::Error,
     interpreter,
     parse::error::ConvertingErrorType::ParsingError,
     vm,
 };
 
+mod watch;
+
+use watch::SourceWatcher;
+
 #[derive(Debug)]
 pub enum Command {
     OpenMenu,
 }
 
+/// Exit status of the process, when awa code panics or fails with
+/// a runtime error, that cannot be recovered from
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+/// Amount of frames, shown at each end of a long backtrace
+const BACKTRACE_EDGE_FRAMES: usize = 10;
+
+/// Exit status of the process, when execution is interrupted with Ctrl+C
+pub const INTERRUPT_EXIT_CODE: i32 = 130;
+
+/// How often the source file is checked for changes with `--watch`
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Settings of the awa program run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions {
+    /// Do not optimize hot functions and loops
+    pub unoptimized: bool,
+    /// Print values of local variables in backtraces
+    pub backtrace_locals: bool,
+    /// Roll heap objects back along with the stack on backoff
+    pub heap_snapshots: bool,
+    /// Maximum amount of nested calls, `vm::DEFAULT_MAX_CALL_DEPTH` if not set
+    pub max_call_depth: Option<usize>,
+    /// Maximum amount of instructions to run, unlimited if not set
+    pub max_instructions: Option<u64>,
+    /// Maximum amount of objects on the heap, unlimited if not set
+    pub max_heap_objects: Option<usize>,
+    /// Check the source file for saved changes this often and hotswap them,
+    /// not watched if not set
+    pub watch_interval: Option<Duration>,
+}
+
 pub enum BackwardsCommunication {
     Hotswapped,
     ReturnedToExecution,
     RequireHotswap,
-    Finished,
+    /// Execution is over, contains exit status of the process
+    Finished(i32),
 }
 
 /// Create bytecode and run typed AST module in VM
 ///
+/// `path` and `src` of the module are used to report runtime errors
+///
 /// # Panics
 ///
 /// Will panic in case of failed backwards communication via mpsc
 pub fn run(
+    path: &Utf8PathBuf,
+    src: &EcoString,
     analyzer: &mut TypeAnalyzer,
     module: &module::Typed,
-    unoptimized: bool,
+    options: RunOptions,
     command_receiver: &std::sync::mpsc::Receiver<Command>,
     backwards_sender: &std::sync::mpsc::Sender<BackwardsCommunication>,
 ) {
-    let bytecode = make_bytecode(module);
-    let mut vm = vm::VM::new(bytecode, !unoptimized);
-    let mut awaiting_hotswap = false;
+    let mut vm = create_vm(module, options);
+    // NOTE: path and code of each source, indexed as in `vm::CodeLocation`
+    let mut sources: Vec<(Utf8PathBuf, EcoString)> = vec![(path.clone(), src.clone())];
+    let mut function_sources = get_function_sources(module, src);
+    // NOTE: name of the failed function, execution is rolled back after its hotswap
+    let mut awaiting_hotswap: Option<EcoString> = None;
+    let mut watcher = options
+        .watch_interval
+        .map(|interval| SourceWatcher::new(path, src, module, interval));
 
     loop {
+        if let Some(watcher) = watcher.as_mut() {
+            let updated_src = watcher.poll();
+            let hotswap = updated_src.as_ref().map(|updated_src| {
+                apply_source_update(&mut vm, analyzer, watcher, &mut sources, updated_src)
+            });
+            let hotswap = match (hotswap, updated_src) {
+                (Some(Ok(Some(hotswap))), Some(updated_src)) => {
+                    function_sources.extend(get_function_sources(&hotswap.module, &updated_src));
+
+                    Some(hotswap)
+                }
+                (Some(Err(error)), _) => {
+                    print_runtime_error(&sources, error.get_description(), None);
+
+                    let () = backwards_sender
+                        .send(BackwardsCommunication::Finished(PANIC_EXIT_CODE))
+                        .unwrap();
+                    return;
+                }
+                _ => None,
+            };
+
+            // NOTE: user is not prompted for the frame here, as menu is not open
+            if let Some(hotswap) = hotswap {
+                let caller_frame = get_caller_frame(&vm, &hotswap.restarted_callers);
+
+                // NOTE: failed call would fail again, unless the update changes it or restarts its caller
+                if let Some(function_name) = awaiting_hotswap.take() {
+                    if hotswap.restarted_callers.is_empty()
+                        && !swaps_function(&hotswap.module, &function_name)
+                    {
+                        eprintln!("`{function_name}` is not changed, still awaiting its hotswap");
+                        awaiting_hotswap = Some(function_name);
+                    } else {
+                        let _ = vm.perform_backoff_to(caller_frame.unwrap_or(0));
+                    }
+                } else if let Some(caller_frame) = caller_frame {
+                    restart_caller(&mut vm, caller_frame);
+                }
+            }
+        }
+
         if let Ok(command) = command_receiver.try_recv() {
             match command {
                 Command::OpenMenu => {
@@ -49,11 +144,20 @@ pub fn run(
 
                     match decision {
                         MenuAction::PerformHotswap => {
-                            let user_input = cli::input::get_user_input();
+                            let predefined_text = awaiting_hotswap
+                                .as_ref()
+                                .and_then(|function_name| function_sources.get(function_name));
+                            let user_input =
+                                cli::input::get_user_input(predefined_text.map(String::as_str));
 
-                            let module = analyzer.handle_hotswap(&user_input);
-                            let module = match module {
-                                Ok(module) => module,
+                            let call_stack = vm
+                                .backtrace()
+                                .into_iter()
+                                .map(|frame| frame.function_name)
+                                .collect::<Vec<_>>();
+                            let hotswap = analyzer.handle_hotswap(&user_input, &call_stack);
+                            let hotswap = match hotswap {
+                                Ok(hotswap) => hotswap,
                                 Err(err) => {
                                     print_diagnostics(
                                         "hotswap.awa".into(),
@@ -61,24 +165,41 @@ pub fn run(
                                         &err,
                                     );
 
-                                    if awaiting_hotswap {
-                                        awaiting_hotswap = false;
-                                    }
+                                    // NOTE: failed call cannot go on, until it is hotswapped
+                                    let confirmation = if awaiting_hotswap.is_some() {
+                                        BackwardsCommunication::RequireHotswap
+                                    } else {
+                                        BackwardsCommunication::Hotswapped
+                                    };
 
-                                    let () = backwards_sender
-                                        .send(BackwardsCommunication::Hotswapped)
-                                        .unwrap();
+                                    let () = backwards_sender.send(confirmation).unwrap();
 
                                     continue;
                                 }
                             };
 
-                            let hotswap_bytecode = make_bytecode(&module);
+                            let (hotswap_bytecode, hotswap_locations) =
+                                make_bytecode(&hotswap.module);
 
-                            vm.hotswap_function(&hotswap_bytecode);
+                            if let Err(error) = vm.hotswap_structs(&hotswap_bytecode) {
+                                print_runtime_error(&sources, error.get_description(), None);
 
-                            if awaiting_hotswap {
-                                awaiting_hotswap = false;
+                                let () = backwards_sender
+                                    .send(BackwardsCommunication::Finished(PANIC_EXIT_CODE))
+                                    .unwrap();
+                                return;
+                            }
+                            vm.hotswap_functions(&hotswap_bytecode, &hotswap_locations);
+                            sources.push(("hotswap.awa".into(), user_input.as_str().into()));
+                            function_sources
+                                .extend(get_function_sources(&hotswap.module, &user_input));
+
+                            let caller_frame = get_caller_frame(&vm, &hotswap.restarted_callers);
+
+                            if awaiting_hotswap.take().is_some() {
+                                restart_frame(&mut vm, &sources, caller_frame.unwrap_or(0));
+                            } else if let Some(caller_frame) = caller_frame {
+                                restart_caller(&mut vm, caller_frame);
                             }
 
                             let () = backwards_sender
@@ -92,7 +213,7 @@ pub fn run(
                         }
                         MenuAction::CtrlC => {
                             let () = backwards_sender
-                                .send(BackwardsCommunication::Finished)
+                                .send(BackwardsCommunication::Finished(INTERRUPT_EXIT_CODE))
                                 .unwrap();
                             println!("recieved SIGINT");
 
@@ -103,24 +224,63 @@ pub fn run(
             }
         }
 
-        if !awaiting_hotswap {
+        if awaiting_hotswap.is_none() {
             let backoff_message = vm.run();
 
             if let Some(backoff_message) = backoff_message {
                 match backoff_message {
-                    vm::RunCommunication::RequireHotswap(backoff_message) => {
+                    vm::RunCommunication::RuntimeError(error) => {
+                        let reason = error.get_description();
+                        let location = vm.location_at(error.get_program_counter());
+
+                        let backtrace = vm.backtrace();
+                        print_runtime_error(&sources, reason.clone(), location);
+                        print_backtrace(&sources, &backtrace, options.backtrace_locals);
+
+                        // NOTE: failure in `main()` has no call to roll back to
+                        let function_name = backtrace
+                            .first()
+                            .filter(|_| error.is_recoverable() && backtrace.len() > 1)
+                            .map(|frame| frame.function_name.clone());
+
+                        let Some(function_name) = function_name else {
+                            let () = backwards_sender
+                                .send(BackwardsCommunication::Finished(PANIC_EXIT_CODE))
+                                .unwrap();
+                            return;
+                        };
+
                         println!(
-                            "recieved bacoff message: `{backoff_message}`. consider hotswapping"
+                            "recieved backoff message: `{reason}`. consider hotswapping `{function_name}`"
                         );
-                        awaiting_hotswap = true;
+                        awaiting_hotswap = Some(function_name);
 
                         let () = backwards_sender
                             .send(BackwardsCommunication::RequireHotswap)
                             .unwrap();
                     }
+                    vm::RunCommunication::Panicked {
+                        message,
+                        location,
+                        backtrace,
+                    } => {
+                        print_runtime_error(&sources, format!("panicked: {message}"), location);
+                        print_backtrace(&sources, &backtrace, options.backtrace_locals);
+
+                        let () = backwards_sender
+                            .send(BackwardsCommunication::Finished(PANIC_EXIT_CODE))
+                            .unwrap();
+                        return;
+                    }
+                    vm::RunCommunication::Exited(code) => {
+                        let () = backwards_sender
+                            .send(BackwardsCommunication::Finished(code.into()))
+                            .unwrap();
+                        return;
+                    }
                     vm::RunCommunication::Finished => {
                         let () = backwards_sender
-                            .send(BackwardsCommunication::Finished)
+                            .send(BackwardsCommunication::Finished(0))
                             .unwrap();
                         return;
                     }
@@ -130,6 +290,173 @@ pub fn run(
     }
 }
 
+/// Create bytecode and run typed AST module in VM straight through, without
+/// menu. Functions are hotswapped only from the watched source file
+///
+/// `path` and `src` of the module are used to report runtime errors
+///
+/// Returns exit status of the process: `0` on finish, the code passed to
+/// `exit(code)`, or `PANIC_EXIT_CODE` on panic and runtime error
+#[must_use]
+pub fn run_batch(
+    path: &Utf8PathBuf,
+    src: &EcoString,
+    analyzer: &mut TypeAnalyzer,
+    module: &module::Typed,
+    options: RunOptions,
+) -> i32 {
+    let mut vm = create_vm(module, options);
+    let mut sources = vec![(path.clone(), src.clone())];
+    let mut watcher = options
+        .watch_interval
+        .map(|interval| SourceWatcher::new(path, src, module, interval));
+
+    loop {
+        if let Some(watcher) = watcher.as_mut() {
+            let hotswap = watcher.poll().map_or(Ok(None), |updated_src| {
+                apply_source_update(&mut vm, analyzer, watcher, &mut sources, &updated_src)
+            });
+            let hotswap = match hotswap {
+                Ok(hotswap) => hotswap,
+                Err(error) => {
+                    print_runtime_error(&sources, error.get_description(), None);
+
+                    return PANIC_EXIT_CODE;
+                }
+            };
+
+            if let Some(hotswap) = hotswap {
+                if let Some(caller_frame) = get_caller_frame(&vm, &hotswap.restarted_callers) {
+                    restart_caller(&mut vm, caller_frame);
+                }
+            }
+        }
+
+        let Some(communication) = vm.run() else {
+            continue;
+        };
+
+        match communication {
+            vm::RunCommunication::RuntimeError(error) => {
+                let location = vm.location_at(error.get_program_counter());
+
+                print_runtime_error(&sources, error.get_description(), location);
+                print_backtrace(&sources, &vm.backtrace(), options.backtrace_locals);
+
+                return PANIC_EXIT_CODE;
+            }
+            vm::RunCommunication::Panicked {
+                message,
+                location,
+                backtrace,
+            } => {
+                print_runtime_error(&sources, format!("panicked: {message}"), location);
+                print_backtrace(&sources, &backtrace, options.backtrace_locals);
+
+                return PANIC_EXIT_CODE;
+            }
+            vm::RunCommunication::Exited(code) => return code.into(),
+            vm::RunCommunication::Finished => return 0,
+        }
+    }
+}
+
+/// Hotswap functions and structs, changed in the saved source file, into
+/// the running program
+///
+/// The whole updated program is type checked, then its changed definitions
+/// are checked against the running ones and hotswapped all at once. Rejected
+/// update is reported, and the program keeps running as is
+///
+/// Returns module of the hotswapped definitions along with the callers, which
+/// calls have to be restarted, if there are any
+///
+/// # Errors
+///
+/// Returns `RuntimeError`, if live instances of the changed structs cannot
+/// be migrated, so that the program cannot go on
+fn apply_source_update(
+    vm: &mut vm::VM,
+    analyzer: &mut TypeAnalyzer,
+    watcher: &mut SourceWatcher,
+    sources: &mut Vec<(Utf8PathBuf, EcoString)>,
+    src: &EcoString,
+) -> Result<Option<Hotswap>, vm::error::RuntimeError> {
+    let path = watcher.get_path().clone();
+    let mut updated_analyzer = TypeAnalyzer::new();
+
+    let module = match updated_analyzer.analyze_input(src) {
+        Ok(module) => module,
+        Err(err) => {
+            print_diagnostics(path, src.clone(), &err);
+            return Ok(None);
+        }
+    };
+
+    let changed_definitions = watcher.get_changed(&module, src);
+    let call_stack = vm
+        .backtrace()
+        .into_iter()
+        .map(|frame| frame.function_name)
+        .collect::<Vec<_>>();
+    let restarted_callers =
+        match analyzer.accept_update(updated_analyzer, &changed_definitions, &call_stack) {
+            Ok(restarted_callers) => restarted_callers,
+            Err(err) => {
+                print_diagnostics(path, src.clone(), &err);
+                return Ok(None);
+            }
+        };
+
+    let changed_definitions = changed_definitions.into_iter().cloned().collect::<Vec<_>>();
+    let Ok(changed_definitions) = Vec1::try_from_vec(changed_definitions) else {
+        return Ok(None);
+    };
+    let unit = Module {
+        name: module.name.clone(),
+        definitions: Some(changed_definitions),
+    };
+    watcher.accept(&module, src);
+
+    let (hotswap_bytecode, hotswap_locations) = make_bytecode(&unit);
+
+    vm.hotswap_structs(&hotswap_bytecode)?;
+    vm.hotswap_functions(&hotswap_bytecode, &hotswap_locations);
+    sources.push((path.clone(), src.clone()));
+
+    for definition in unit.definitions.iter().flatten() {
+        let (DefinitionTyped::Function { name, .. }
+        | DefinitionTyped::Struct { name, .. }
+        | DefinitionTyped::Enum { name, .. }) = definition;
+
+        eprintln!("hotswapped `{name}` from {path}");
+    }
+
+    Ok(Some(Hotswap {
+        module: unit,
+        restarted_callers,
+    }))
+}
+
+/// Create VM for the module, configured with the run options
+fn create_vm(module: &module::Typed, options: RunOptions) -> vm::VM {
+    let (bytecode, locations) = make_bytecode(module);
+    let mut vm = vm::VM::new_with_locations(bytecode, locations, !options.unoptimized);
+
+    vm.set_heap_snapshots(options.heap_snapshots);
+    if let Some(max_call_depth) = options.max_call_depth {
+        vm.set_max_call_depth(max_call_depth);
+    }
+    if let Some(max_instructions) = options.max_instructions {
+        vm.set_fuel(max_instructions);
+    }
+    if let Some(max_heap_objects) = options.max_heap_objects {
+        vm.set_max_heap_size(max_heap_objects);
+    }
+
+    vm
+}
+
 #[must_use]
 pub fn build_ast(
     path: Utf8PathBuf,
@@ -147,13 +474,34 @@ pub fn build_ast(
     }
 }
 
+/// Create bytecode of the module, along with location of each instruction
 #[must_use]
-pub fn make_bytecode(module: &Module<DefinitionTyped>) -> Vec<vm::instruction::Instruction> {
+pub fn make_bytecode(
+    module: &Module<DefinitionTyped>,
+) -> (vm::instruction::Bytecode, vm::instruction::Locations) {
     let interpreter = interpreter::Interpreter::new();
 
     interpreter.interpret_module(module)
 }
 
+/// Source code of each function in the module, used as a starting point of its hotswap
+fn get_function_sources(module: &module::Typed, src: &str) -> HashMap<EcoString, String> {
+    module
+        .definitions
+        .iter()
+        .flatten()
+        .filter_map(|definition| match definition {
+            DefinitionTyped::Function { name, location, .. } => {
+                let source = src.get(location.start as usize..location.end as usize)?;
+
+                // NOTE: function location starts at its name, after `func` keyword
+                Some((name.clone(), format!("func {source}")))
+            }
+            DefinitionTyped::Struct { .. } | DefinitionTyped::Enum { .. } => None,
+        })
+        .collect()
+}
+
 fn print_diagnostics(
     path: Utf8PathBuf,
     src: ecow::EcoString,
@@ -172,11 +520,162 @@ fn print_diagnostics(
         },
     };
 
+    print_diagnostic_list(&error.to_diagnostics());
+}
+
+/// Roll execution back to the call, chosen by user among the active ones,
+/// starting from `first_frame`
+fn restart_frame(vm: &mut vm::VM, sources: &[(Utf8PathBuf, EcoString)], first_frame: usize) {
+    let backtrace = vm.backtrace();
+
+    // NOTE: `main()` is the last frame, and it has no call to roll back to
+    let indices = get_shown_frames(backtrace.len())
+        .filter(|&index| index >= first_frame && index + 1 < backtrace.len())
+        .collect::<Vec<_>>();
+    let frames = indices
+        .iter()
+        .map(|&index| {
+            let frame = &backtrace[index];
+
+            format!(
+                "{index}: {}{}",
+                frame.function_name,
+                format_position(sources, frame.location).unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let frame = if frames.len() > 1 {
+        indices[cli::input::get_user_frame_decision(frames)]
+    } else {
+        first_frame
+    };
+
+    let _ = vm.perform_backoff_to(frame);
+}
+
+/// Index of the outermost frame, that runs one of the callers, which calls
+/// have to be restarted after the hotswap
+fn get_caller_frame(vm: &vm::VM, restarted_callers: &[EcoString]) -> Option<usize> {
+    vm.backtrace()
+        .iter()
+        .rposition(|frame| restarted_callers.contains(&frame.function_name))
+}
+
+/// Whether the hotswapped module replaces the function
+fn swaps_function(module: &Module<DefinitionTyped>, function_name: &EcoString) -> bool {
+    module.definitions.iter().flatten().any(|definition| {
+        matches!(definition, DefinitionTyped::Function { name, .. } if name == function_name)
+    })
+}
+
+/// Restart call of the caller, which still runs code of the replaced function signature
+fn restart_caller(vm: &mut vm::VM, frame: usize) {
+    if let Some(function_name) = vm.perform_backoff_to(frame) {
+        eprintln!("restarted call of `{function_name}` after the hotswap");
+    }
+}
+
+/// Indices of the frames, worth showing out of the backtrace of given length:
+/// all of them, or both ends of it, if it is too long, e.g. on stack overflow
+fn get_shown_frames(frames_count: usize) -> impl Iterator<Item = usize> {
+    let trimmed = frames_count > 2 * BACKTRACE_EDGE_FRAMES;
+    let head = if trimmed {
+        BACKTRACE_EDGE_FRAMES
+    } else {
+        frames_count
+    };
+    let tail = if trimmed {
+        frames_count - BACKTRACE_EDGE_FRAMES
+    } else {
+        frames_count
+    };
+
+    (0..head).chain(tail..frames_count)
+}
+
+/// Report runtime error, labeling the awa code it occurred in, if its location is known
+fn print_runtime_error(
+    sources: &[(Utf8PathBuf, EcoString)],
+    text: String,
+    location: Option<vm::CodeLocation>,
+) {
+    let Some((location, (path, src))) =
+        location.and_then(|location| Some((location.location, sources.get(location.source)?)))
+    else {
+        eprintln!("error: {text}");
+        return;
+    };
+
+    let diagnostic = Diagnostic {
+        text,
+        location: diagnostic::Location {
+            src: src.clone(),
+            path: path.clone(),
+            location,
+        },
+    };
+
+    print_diagnostic_list(&[diagnostic]);
+}
+
+/// Report awa call stack, with the call site of each function, if its location is known
+fn print_backtrace(
+    sources: &[(Utf8PathBuf, EcoString)],
+    backtrace: &[vm::StackFrame],
+    with_locals: bool,
+) {
+    eprintln!("backtrace:");
+
+    let mut previous_index = None;
+
+    for index in get_shown_frames(backtrace.len()) {
+        let frame = &backtrace[index];
+        let position = format_position(sources, frame.location);
+
+        let omitted = index - previous_index.map_or(0, |previous| previous + 1);
+        if omitted > 0 {
+            eprintln!("      ... {omitted} frames omitted");
+        }
+        previous_index = Some(index);
+
+        eprintln!(
+            "{index:>4}: {}{}",
+            frame.function_name,
+            position.unwrap_or_default()
+        );
+
+        if with_locals {
+            for (name, value) in &frame.locals {
+                eprintln!("          {name} = {value}");
+            }
+        }
+    }
+}
+
+/// Format location as ` at path:line:column`, if it is known
+fn format_position(
+    sources: &[(Utf8PathBuf, EcoString)],
+    location: Option<vm::CodeLocation>,
+) -> Option<String> {
+    let location = location?;
+    let (path, src) = sources.get(location.source)?;
+    let preceding = src.get(..location.location.start as usize)?;
+
+    let line = preceding.matches('\n').count() + 1;
+    let column = preceding
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+
+    Some(format!(" at {path}:{line}:{column}"))
+}
+
+fn print_diagnostic_list(diagnostics: &[Diagnostic]) {
     let buffer_writer = termcolor::BufferWriter::stderr(termcolor::ColorChoice::Auto);
     let mut buffer = buffer_writer.buffer();
 
-    let diagnostics = error.to_diagnostics();
-
     for diagnostic in diagnostics {
         diagnostic.write(&mut buffer);
     }
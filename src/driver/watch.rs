@@ -0,0 +1,213 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+
+use crate::ast::{definition::DefinitionTyped, module};
+
+/// Amount of polls between clock readings, so that VM loop is not slowed down by them
+const POLLS_PER_CLOCK_READING: u32 = 1024;
+
+/// Source file of the running program, checked for saved changes
+pub(super) struct SourceWatcher {
+    path: Utf8PathBuf,
+    interval: Duration,
+    last_check: Instant,
+    polls: u32,
+    modified: Option<SystemTime>,
+    /// Last read contents of the file
+    src: EcoString,
+    /// Source code of each definition of the running program
+    definitions: HashMap<EcoString, EcoString>,
+}
+
+impl SourceWatcher {
+    pub(super) fn new(
+        path: &Utf8PathBuf,
+        src: &EcoString,
+        module: &module::Typed,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            path: path.clone(),
+            interval,
+            last_check: Instant::now(),
+            polls: 0,
+            modified: get_modification_time(path),
+            src: src.clone(),
+            definitions: get_definition_sources(module, src),
+        }
+    }
+
+    pub(super) fn get_path(&self) -> &Utf8PathBuf {
+        &self.path
+    }
+
+    /// Contents of the file, if it is saved with changes since the last check
+    pub(super) fn poll(&mut self) -> Option<EcoString> {
+        self.polls = (self.polls + 1) % POLLS_PER_CLOCK_READING;
+        if self.polls != 0 || self.last_check.elapsed() < self.interval {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let modified = get_modification_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        // NOTE: file may be unreadable in the middle of the save, next save is awaited then
+        let src = std::fs::read_to_string(&self.path).ok()?;
+        if src == self.src {
+            return None;
+        }
+        self.src = src.into();
+
+        Some(self.src.clone())
+    }
+
+    /// Definitions of the updated module, that differ from the running ones
+    pub(super) fn get_changed<'a>(
+        &self,
+        module: &'a module::Typed,
+        src: &str,
+    ) -> Vec<&'a DefinitionTyped> {
+        module
+            .definitions
+            .iter()
+            .flatten()
+            .filter(|definition| {
+                let (name, source) = get_definition_source(definition, src);
+
+                self.definitions.get(name).map(EcoString::as_str) != source
+            })
+            .collect()
+    }
+
+    /// Remember definitions of the updated module as running ones
+    pub(super) fn accept(&mut self, module: &module::Typed, src: &str) {
+        self.definitions = get_definition_sources(module, src);
+    }
+}
+
+fn get_modification_time(path: &Utf8PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn get_definition_sources(module: &module::Typed, src: &str) -> HashMap<EcoString, EcoString> {
+    module
+        .definitions
+        .iter()
+        .flatten()
+        .filter_map(|definition| {
+            let (name, source) = get_definition_source(definition, src);
+
+            Some((name.clone(), source?.into()))
+        })
+        .collect()
+}
+
+/// Name of the definition and its code, which spans from the name to the closing brace
+fn get_definition_source<'a, 'b>(
+    definition: &'a DefinitionTyped,
+    src: &'b str,
+) -> (&'a EcoString, Option<&'b str>) {
+    let (DefinitionTyped::Function { name, location, .. }
+    | DefinitionTyped::Struct { name, location, .. }
+    | DefinitionTyped::Enum { name, location, .. }) = definition;
+
+    (
+        name,
+        src.get(location.start as usize..location.end as usize),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ecow::EcoString;
+
+    use crate::ast::{analyzer::TypeAnalyzer, definition::DefinitionTyped, module};
+
+    use super::{get_definition_source, SourceWatcher};
+
+    const SRC: &str = "func helper() int { return 1 }
+
+func main() { println(helper()) }
+";
+
+    fn analyze(src: &str) -> module::Typed {
+        TypeAnalyzer::new()
+            .analyze_input(src)
+            .expect("should type check")
+    }
+
+    fn get_names(definitions: &[&DefinitionTyped]) -> Vec<EcoString> {
+        definitions
+            .iter()
+            .map(|definition| {
+                let (DefinitionTyped::Function { name, .. }
+                | DefinitionTyped::Struct { name, .. }
+                | DefinitionTyped::Enum { name, .. }) = definition;
+
+                name.clone()
+            })
+            .collect()
+    }
+
+    fn new_watcher() -> SourceWatcher {
+        SourceWatcher::new(
+            &"main.awa".into(),
+            &SRC.into(),
+            &analyze(SRC),
+            Duration::ZERO,
+        )
+    }
+
+    #[test]
+    fn test_definition_source() {
+        let module = analyze(SRC);
+        let helper = module.definitions.iter().flatten().next().unwrap();
+
+        assert_eq!(
+            get_definition_source(helper, SRC),
+            (&"helper".into(), Some("helper() int { return 1 }"))
+        );
+    }
+
+    #[test]
+    fn test_moved_definition_is_not_changed() {
+        let src = "
+
+func main() { println(helper()) }
+
+func helper() int { return 1 }
+";
+        let module = analyze(src);
+
+        assert!(new_watcher().get_changed(&module, src).is_empty());
+    }
+
+    #[test]
+    fn test_added_and_edited_definitions_are_changed() {
+        let src = "func helper() int { return 2 }
+
+func extra() {}
+
+func main() { println(helper()) }
+";
+        let module = analyze(src);
+
+        assert_eq!(
+            get_names(&new_watcher().get_changed(&module, src)),
+            vec![EcoString::from("helper"), EcoString::from("extra")]
+        );
+    }
+}
@@ -1,22 +1,36 @@
 use std::collections::HashMap;
 
 use ecow::EcoString;
+use vec1::Vec1;
 
 use crate::{
     ast::{
-        definition::DefinitionTyped, expression::TypedExpression, module::Module,
-        operator::BinaryOperator, reassignment::TypedReassignmentTarget, statement::TypedStatement,
+        definition::DefinitionTyped,
+        expression::TypedExpression,
+        iterable::TypedIterable,
+        location::Location,
+        module::Module,
+        operator::{BinaryOperator, UnaryOperator},
+        pattern::{TypedPattern, WILDCARD},
+        reassignment::TypedReassignmentTarget,
+        statement::TypedStatement,
     },
     type_::Type,
-    vm::instruction::{Bytecode, Instruction, Value},
+    vm::instruction::{Bytecode, Instruction, Locations, Value},
 };
 
 pub struct Interpreter {
     bytecode: Bytecode,
+    locations: Locations,
+    /// Locations of the statements and expressions being interpreted, innermost last
+    location_stack: Vec<Location>,
     current_func: Option<EcoString>,
     loop_end_stack: Vec<usize>,
     loop_start_stack: Vec<usize>,
-    loop_break_stack: Vec<usize>,
+    loop_break_stack: Vec<Vec<usize>>,
+    loop_continue_stack: Vec<Vec<usize>>,
+    match_count: usize,
+    for_count: usize,
 }
 
 impl Default for Interpreter {
@@ -30,15 +44,22 @@ impl Interpreter {
     pub fn new() -> Self {
         Self {
             bytecode: Vec::new(),
+            locations: Vec::new(),
+            location_stack: Vec::new(),
             current_func: None,
             loop_end_stack: Vec::new(),
             loop_start_stack: Vec::new(),
             loop_break_stack: Vec::new(),
+            loop_continue_stack: Vec::new(),
+            match_count: 0,
+            for_count: 0,
         }
     }
 
+    /// Produces bytecode of the module along with location of the code, each
+    /// of its instructions was produced from
     #[must_use]
-    pub fn interpret_module(mut self, module: &Module<DefinitionTyped>) -> Bytecode {
+    pub fn interpret_module(mut self, module: &Module<DefinitionTyped>) -> (Bytecode, Locations) {
         if let Some(definitions) = &module.definitions {
             for definition in definitions {
                 if let DefinitionTyped::Struct { name, fields, .. } = definition {
@@ -92,10 +113,14 @@ impl Interpreter {
             }
         }
 
-        self.bytecode
+        self.record_locations();
+
+        (self.bytecode, self.locations)
     }
 
     fn interpret_statement(&mut self, statement: &TypedStatement) {
+        self.enter_location(statement.get_location());
+
         match statement {
             TypedStatement::Expression(expression) => {
                 self.interpret_expression(expression);
@@ -137,26 +162,112 @@ impl Interpreter {
                         .push(Instruction::StoreInMap(array_name.clone()));
                 }
             },
-            TypedStatement::Loop { body, .. } => {
+            TypedStatement::Loop {
+                condition, body, ..
+            } => {
                 let loop_start = self.bytecode.len();
-                self.loop_start_stack.push(loop_start);
 
-                if let Some(statements) = body {
-                    for statement in statements {
-                        self.interpret_statement(statement);
+                let exit_jump = if let Some(condition) = condition {
+                    self.interpret_expression(condition);
+                    let exit_jump = self.bytecode.len();
+                    self.bytecode.push(Instruction::JumpIfFalse(0)); // Placeholder
+
+                    Some(exit_jump)
+                } else {
+                    None
+                };
+
+                self.interpret_loop_body(loop_start, exit_jump, body.as_ref(), Vec::new());
+            }
+            TypedStatement::For {
+                variable_name,
+                iterable,
+                body,
+                ..
+            } => {
+                // NOTE: state is named after the loop, not its variable, so that
+                // nested loops over the same variable name do not share it
+                let loop_name =
+                    get_hidden_variable_name(&"for".into(), &self.for_count.to_string());
+                self.for_count += 1;
+                let index_name = get_hidden_variable_name(&loop_name, "index");
+
+                let (bound_name, load_element) = match iterable {
+                    TypedIterable::Range { start, end, .. } => {
+                        let end_name = get_hidden_variable_name(&loop_name, "end");
+
+                        self.interpret_expression(start);
+                        self.bytecode
+                            .push(Instruction::StoreInMap(index_name.clone()));
+                        self.interpret_expression(end);
+                        self.bytecode
+                            .push(Instruction::StoreInMap(end_name.clone()));
+
+                        (
+                            vec![Instruction::LoadToStack(end_name)],
+                            vec![Instruction::LoadToStack(index_name.clone())],
+                        )
                     }
-                }
+                    TypedIterable::Array { array, .. } => {
+                        let array_name = get_hidden_variable_name(&loop_name, "array");
 
-                self.bytecode.push(Instruction::Jump(loop_start));
-                let loop_end = self.bytecode.len();
-                self.loop_end_stack.push(loop_end);
+                        self.interpret_expression(array);
+                        self.bytecode
+                            .push(Instruction::StoreInMap(array_name.clone()));
+                        self.bytecode.push(Instruction::PushInt(0));
+                        self.bytecode
+                            .push(Instruction::StoreInMap(index_name.clone()));
 
-                if let Some(break_end) = self.loop_break_stack.pop() {
-                    self.bytecode[break_end] = Instruction::Jump(loop_end);
-                }
+                        (
+                            vec![
+                                Instruction::LoadToStack(array_name.clone()),
+                                Instruction::Length,
+                            ],
+                            vec![
+                                Instruction::LoadToStack(array_name),
+                                Instruction::LoadToStack(index_name.clone()),
+                                Instruction::GetByIndex,
+                            ],
+                        )
+                    }
+                };
+
+                let loop_start = self.bytecode.len();
+
+                self.bytecode
+                    .push(Instruction::LoadToStack(index_name.clone()));
+                self.bytecode.extend(bound_name);
+                self.bytecode.push(Instruction::LessInt);
+                let exit_jump = self.bytecode.len();
+                self.bytecode.push(Instruction::JumpIfFalse(0)); // Placeholder
+
+                self.bytecode.extend(load_element);
+                self.bytecode
+                    .push(Instruction::StoreInMap(variable_name.clone()));
+
+                let step = vec![
+                    Instruction::LoadToStack(index_name.clone()),
+                    Instruction::PushInt(1),
+                    Instruction::AddInt,
+                    Instruction::StoreInMap(index_name),
+                ];
+
+                self.interpret_loop_body(loop_start, Some(exit_jump), body.as_ref(), step);
             }
             TypedStatement::Break { .. } => {
-                self.loop_break_stack.push(self.bytecode.len());
+                let break_jump = self.bytecode.len();
+                self.loop_break_stack
+                    .last_mut()
+                    .expect("break outside of a loop")
+                    .push(break_jump);
+                self.bytecode.push(Instruction::Jump(0)); // Placeholder
+            }
+            TypedStatement::Continue { .. } => {
+                let continue_jump = self.bytecode.len();
+                self.loop_continue_stack
+                    .last_mut()
+                    .expect("continue outside of a loop")
+                    .push(continue_jump);
                 self.bytecode.push(Instruction::Jump(0)); // Placeholder
             }
             TypedStatement::Return { value, .. } => {
@@ -210,15 +321,92 @@ impl Interpreter {
                     }
                 }
             }
-            TypedStatement::Todo { .. }
-            | TypedStatement::Panic { .. }
-            | TypedStatement::Exit { .. } => {
-                self.bytecode.push(Instruction::Halt);
+            TypedStatement::Match { value, arms, .. } => {
+                let value_name =
+                    get_hidden_variable_name(&"match".into(), &self.match_count.to_string());
+                self.match_count += 1;
+
+                self.interpret_expression(value);
+                self.bytecode
+                    .push(Instruction::StoreInMap(value_name.clone()));
+
+                let mut end_jumps = Vec::new();
+
+                for arm in arms.iter().flatten() {
+                    self.enter_location(arm.location);
+
+                    let next_arm_jump = match &arm.pattern {
+                        TypedPattern::Variant {
+                            variant_name,
+                            bindings,
+                            ..
+                        } => {
+                            self.bytecode
+                                .push(Instruction::LoadToStack(value_name.clone()));
+                            self.bytecode
+                                .push(Instruction::IsVariant(variant_name.clone()));
+                            let next_arm_jump = self.bytecode.len();
+                            self.bytecode.push(Instruction::JumpIfFalse(0)); // Placeholder
+
+                            for (index, binding) in bindings.iter().flatten().enumerate() {
+                                if binding.name == WILDCARD {
+                                    continue;
+                                }
+
+                                self.bytecode
+                                    .push(Instruction::LoadToStack(value_name.clone()));
+                                self.bytecode.push(Instruction::GetVariantField(index));
+                                self.bytecode
+                                    .push(Instruction::StoreInMap(binding.name.clone()));
+                            }
+
+                            Some(next_arm_jump)
+                        }
+                        TypedPattern::Wildcard { .. } => None,
+                    };
+
+                    if let Some(statements) = &arm.body {
+                        for statement in statements {
+                            self.interpret_statement(statement);
+                        }
+                    }
+
+                    end_jumps.push(self.bytecode.len());
+                    self.bytecode.push(Instruction::Jump(0)); // Placeholder
+
+                    self.exit_location();
+
+                    if let Some(next_arm_jump) = next_arm_jump {
+                        self.bytecode[next_arm_jump] =
+                            Instruction::JumpIfFalse(self.bytecode.len());
+                    }
+                }
+
+                let end = self.bytecode.len();
+                for end_jump in end_jumps {
+                    self.bytecode[end_jump] = Instruction::Jump(end);
+                }
+            }
+            TypedStatement::Todo { .. } => {
+                self.bytecode
+                    .push(Instruction::Backoff("reached todo".into()));
+            }
+            TypedStatement::Panic { message, .. } => {
+                self.interpret_expression(message);
+                self.bytecode.push(Instruction::Panic);
+            }
+            TypedStatement::Exit { code, .. } => {
+                self.interpret_expression(code);
+                self.bytecode.push(Instruction::Exit);
             }
         }
+
+        self.exit_location();
     }
 
     fn interpret_expression(&mut self, expression: &TypedExpression) {
+        self.enter_location(expression.get_location());
+
         match expression {
             TypedExpression::IntLiteral { value, .. } => {
                 self.bytecode.push(Instruction::PushInt(*value));
@@ -232,6 +420,12 @@ impl Interpreter {
             TypedExpression::CharLiteral { value, .. } => {
                 self.bytecode.push(Instruction::PushChar(*value));
             }
+            TypedExpression::BooleanLiteral { value, .. } => {
+                self.bytecode.push(Instruction::PushBool(*value));
+            }
+            TypedExpression::NilLiteral { .. } => {
+                self.bytecode.push(Instruction::PushNil);
+            }
             TypedExpression::VariableValue { name, .. } => {
                 self.bytecode.push(Instruction::LoadToStack(name.clone()));
             }
@@ -251,9 +445,32 @@ impl Interpreter {
                     "println" => self.bytecode.push(Instruction::Println),
                     "append" => self.bytecode.push(Instruction::Append),
                     "pop" => self.bytecode.push(Instruction::Pop),
+                    "wrapping_add" => self.bytecode.push(Instruction::WrappingAddInt),
+                    "wrapping_sub" => self.bytecode.push(Instruction::WrappingSubInt),
+                    "wrapping_mul" => self.bytecode.push(Instruction::WrappingMulInt),
+                    "saturating_add" => self.bytecode.push(Instruction::SaturatingAddInt),
+                    "saturating_sub" => self.bytecode.push(Instruction::SaturatingSubInt),
+                    "saturating_mul" => self.bytecode.push(Instruction::SaturatingMulInt),
                     _ => self.bytecode.push(Instruction::Call(function_name.clone())),
                 }
             }
+            TypedExpression::EnumInitialization {
+                enum_name,
+                variant_name,
+                arguments,
+                ..
+            } => {
+                let arguments = arguments.as_deref().unwrap_or_default();
+                for argument in arguments {
+                    self.interpret_expression(&argument.value);
+                }
+
+                self.bytecode.push(Instruction::NewEnum(
+                    enum_name.clone(),
+                    variant_name.clone(),
+                    arguments.len(),
+                ));
+            }
             TypedExpression::StructFieldAccess {
                 struct_name,
                 field_name,
@@ -332,19 +549,101 @@ impl Interpreter {
                     BinaryOperator::Concatenation => self.bytecode.push(Instruction::Concat),
                     BinaryOperator::And => self.bytecode.push(Instruction::And),
                     BinaryOperator::Or => self.bytecode.push(Instruction::Or),
+                    BinaryOperator::BitwiseAnd => self.bytecode.push(Instruction::BitwiseAnd),
+                    BinaryOperator::BitwiseOr => self.bytecode.push(Instruction::BitwiseOr),
+                    BinaryOperator::BitwiseXor => self.bytecode.push(Instruction::BitwiseXor),
+                    BinaryOperator::ShiftLeft => self.bytecode.push(Instruction::ShiftLeft),
+                    BinaryOperator::ShiftRight => self.bytecode.push(Instruction::ShiftRight),
+                }
+            }
+            TypedExpression::UnaryOperation {
+                operator, operand, ..
+            } => {
+                self.interpret_expression(operand);
+                match operator {
+                    UnaryOperator::Not => self.bytecode.push(Instruction::Not),
+                    UnaryOperator::NegationInt => self.bytecode.push(Instruction::NegateInt),
+                    UnaryOperator::NegationFloat => self.bytecode.push(Instruction::NegateFloat),
                 }
             }
         }
+
+        self.exit_location();
+    }
+
+    /// Emits loop body, followed by the step code and the loop back edge.
+    /// After that fixes up jumps of `break`/`continue` statements and the
+    /// loop exit jump (if loop has one)
+    ///
+    /// Back edge is the only backward jump of the loop, so VM can
+    /// count loop iterations on it
+    fn interpret_loop_body(
+        &mut self,
+        loop_start: usize,
+        exit_jump: Option<usize>,
+        body: Option<&Vec1<TypedStatement>>,
+        step: Vec<Instruction>,
+    ) {
+        self.loop_start_stack.push(loop_start);
+        self.loop_break_stack.push(Vec::new());
+        self.loop_continue_stack.push(Vec::new());
+
+        if let Some(statements) = body {
+            for statement in statements {
+                self.interpret_statement(statement);
+            }
+        }
+
+        let step_start = self.bytecode.len();
+        self.bytecode.extend(step);
+        self.bytecode.push(Instruction::Jump(loop_start));
+        self.loop_start_stack.pop();
+        let loop_end = self.bytecode.len();
+        self.loop_end_stack.push(loop_end);
+
+        if let Some(exit_jump) = exit_jump {
+            self.bytecode[exit_jump] = Instruction::JumpIfFalse(loop_end);
+        }
+
+        for break_jump in self.loop_break_stack.pop().unwrap_or_default() {
+            self.bytecode[break_jump] = Instruction::Jump(loop_end);
+        }
+
+        // NOTE: `continue` jumps forward to the loop step instead of
+        // the loop start, so that the only backward jump of the loop is
+        // the one VM counts iterations and detects hot loop region by
+        for continue_jump in self.loop_continue_stack.pop().unwrap_or_default() {
+            self.bytecode[continue_jump] = Instruction::Jump(step_start);
+        }
+    }
+
+    fn enter_location(&mut self, location: Location) {
+        self.record_locations();
+        self.location_stack.push(location);
+    }
+
+    fn exit_location(&mut self) {
+        self.record_locations();
+        self.location_stack.pop();
+    }
+
+    /// Attributes instructions, emitted since the last record, to the
+    /// innermost statement or expression being interpreted
+    fn record_locations(&mut self) {
+        let location = self.location_stack.last().copied();
+
+        self.locations.resize(self.bytecode.len(), location);
     }
 
     fn default_value_for_type(type_: &Type) -> Value {
         match type_ {
-            Type::Int | Type::Boolean => Value::Int(0),
+            Type::Int => Value::Int(0),
+            Type::Boolean => Value::Bool(false),
             Type::Float => Value::Float(0.0),
             Type::String => Value::String("".into()),
             Type::Char => Value::Char('\0'),
             Type::Array { .. } => Value::Slice(Vec::new()),
-            Type::Void => Value::Nil,
+            Type::Optional { .. } | Type::Enum { .. } | Type::Nil | Type::Void => Value::Nil,
             Type::Custom { name } => Value::Struct {
                 name: name.clone(),
                 fields: HashMap::new(),
@@ -352,3 +651,10 @@ impl Interpreter {
         }
     }
 }
+
+/// Names of hidden variables, that hold `for` loop and `match` state in the function environment
+///
+/// `#` cannot appear in awa names, so those never clash with user variables
+fn get_hidden_variable_name(variable_name: &EcoString, suffix: &str) -> EcoString {
+    format!("{variable_name}#{suffix}").into()
+}
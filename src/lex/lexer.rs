@@ -156,6 +156,14 @@ where
                 self.lex_pipe();
                 Ok(())
             }
+            '^' => {
+                self.lex_single_char(Token::Caret);
+                Ok(())
+            }
+            '?' => {
+                self.lex_single_char(Token::Question);
+                Ok(())
+            }
             '!' => {
                 self.lex_bang();
                 Ok(())
@@ -169,7 +177,7 @@ where
                 Ok(())
             }
             '.' => {
-                self.lex_single_char(Token::Dot);
+                self.lex_dot();
                 Ok(())
             }
             ',' => {
@@ -205,7 +213,7 @@ where
                 Ok(())
             }
             ':' => {
-                self.lex_single_char(Token::Colon);
+                self.lex_colon();
                 Ok(())
             }
             '\n' | ' ' | '\t' | '\x0c' => {
@@ -252,12 +260,18 @@ where
         let mut last_is_digit = true;
 
         loop {
+            let is_range_next = self.current_char == Some('.') && self.peek_char() == Some('.');
+
             match self.current_char {
                 Some(ch) if ch.is_ascii_digit() || ch == '-' => {
                     let _ = self.advance_char();
                     number.push(ch);
                     last_is_digit = true;
                 }
+                // NOTE: `..` after an integer is a range, not a floating point
+                Some('.') if !has_floating_point && is_range_next => {
+                    break;
+                }
                 Some('.') => {
                     let _ = self.advance_char();
                     if number.is_empty() || has_floating_point {
@@ -291,7 +305,7 @@ where
                         },
                     });
                 }
-            };
+            }
         }
 
         if number.is_empty() || !last_is_digit {
@@ -566,7 +580,7 @@ where
                         end: end_location,
                     },
                 });
-            };
+            }
 
             self.emit(TokenSpan {
                 start: token_start,
@@ -760,6 +774,54 @@ where
         }
     }
 
+    fn lex_dot(&mut self) {
+        let token_start = self.current_location;
+        let _ = self.advance_char();
+
+        if let Some('.') = self.current_char {
+            let _ = self.advance_char();
+            let token_end = self.current_location;
+
+            self.emit(TokenSpan {
+                start: token_start,
+                end: token_end,
+                token: Token::DotDot,
+            });
+        } else {
+            let token_end = self.current_location;
+
+            self.emit(TokenSpan {
+                start: token_start,
+                end: token_end,
+                token: Token::Dot,
+            });
+        }
+    }
+
+    fn lex_colon(&mut self) {
+        let token_start = self.current_location;
+        let _ = self.advance_char();
+
+        if let Some(':') = self.current_char {
+            let _ = self.advance_char();
+            let token_end = self.current_location;
+
+            self.emit(TokenSpan {
+                start: token_start,
+                end: token_end,
+                token: Token::ColonColon,
+            });
+        } else {
+            let token_end = self.current_location;
+
+            self.emit(TokenSpan {
+                start: token_start,
+                end: token_end,
+                token: Token::Colon,
+            });
+        }
+    }
+
     fn lex_bang(&mut self) {
         let token_start = self.current_location;
         let _ = self.advance_char();
@@ -960,9 +1022,14 @@ fn to_keyword(word: &str) -> Option<Token> {
     match word {
         "var" => Some(Token::Var),
         "struct" => Some(Token::Struct),
+        "enum" => Some(Token::Enum),
+        "match" => Some(Token::Match),
         "func" => Some(Token::Func),
         "loop" => Some(Token::Loop),
+        "for" => Some(Token::For),
+        "in" => Some(Token::In),
         "break" => Some(Token::Break),
+        "continue" => Some(Token::Continue),
         "if" => Some(Token::If),
         "else" => Some(Token::Else),
         "return" => Some(Token::Return),
@@ -972,6 +1039,7 @@ fn to_keyword(word: &str) -> Option<Token> {
         "int" => Some(Token::Int),
         "float" => Some(Token::Float),
         "char" => Some(Token::Char),
+        "bool" => Some(Token::Bool),
         "string" => Some(Token::String),
         _ => None,
     }
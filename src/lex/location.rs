@@ -5,11 +5,13 @@ pub struct Location {
 }
 
 impl Location {
-    #[must_use] pub fn new(start: u32, end: u32) -> Self {
+    #[must_use]
+    pub fn new(start: u32, end: u32) -> Self {
         Self { start, end }
     }
 
-    #[must_use] pub fn contains(self, byte_index: u32) -> bool {
+    #[must_use]
+    pub fn contains(self, byte_index: u32) -> bool {
         byte_index >= self.start && byte_index <= self.end
     }
 }
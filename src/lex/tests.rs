@@ -254,11 +254,18 @@ fn test_float_literal_lexing_failed() {
             ],
         },
         TestCase {
-            input: "123..",
-            expected: vec![Err(LexicalError {
-                error: Type::InvalidNumberFormat,
-                location: Location { start: 0, end: 4 },
-            })],
+            input: "1.2..",
+            expected: vec![
+                Err(LexicalError {
+                    error: Type::InvalidNumberFormat,
+                    location: Location { start: 0, end: 4 },
+                }),
+                Ok(TokenSpan {
+                    token: Token::Dot,
+                    start: 4,
+                    end: 4,
+                }),
+            ],
         },
     ];
 
@@ -301,3 +308,71 @@ fn test_comment() {
         assert_eq!(lex, case.expected);
     }
 }
+
+#[test]
+fn test_bitwise_operators() {
+    let cases = vec![TestCase {
+        input: "& | ^ << >>",
+        expected: vec![
+            Ok(TokenSpan {
+                token: Token::Ampersand,
+                start: 0,
+                end: 1,
+            }),
+            Ok(TokenSpan {
+                token: Token::Pipe,
+                start: 2,
+                end: 3,
+            }),
+            Ok(TokenSpan {
+                token: Token::Caret,
+                start: 4,
+                end: 5,
+            }),
+            Ok(TokenSpan {
+                token: Token::LessLess,
+                start: 6,
+                end: 8,
+            }),
+            Ok(TokenSpan {
+                token: Token::GreaterGreater,
+                start: 9,
+                end: 10,
+            }),
+        ],
+    }];
+
+    for case in cases {
+        let lex = lex(case.input).collect_vec();
+        assert_eq!(lex, case.expected);
+    }
+}
+
+#[test]
+fn test_range_lexing() {
+    let cases = vec![TestCase {
+        input: "0..10",
+        expected: vec![
+            Ok(TokenSpan {
+                token: Token::IntLiteral { value: "0".into() },
+                start: 0,
+                end: 1,
+            }),
+            Ok(TokenSpan {
+                token: Token::DotDot,
+                start: 1,
+                end: 3,
+            }),
+            Ok(TokenSpan {
+                token: Token::IntLiteral { value: "10".into() },
+                start: 3,
+                end: 4,
+            }),
+        ],
+    }];
+
+    for case in cases {
+        let lex = lex(case.input).collect_vec();
+        assert_eq!(lex, case.expected);
+    }
+}
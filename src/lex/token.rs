@@ -33,6 +33,7 @@ pub enum Token {
     GreaterEqualFloat,  // '>=.'
     Concat,             // '<>'
     Colon,              // ':'
+    ColonColon,         // '::'
     Comma,              // ','
     Bang,               // '!'
     Equal,              // '='
@@ -42,23 +43,32 @@ pub enum Token {
     PipePipe,           // '||'
     Ampersand,          // '&'
     AmpersandAmpersand, // '&&'
+    Caret,              // '^'
+    Question,           // '?'
     LessLess,           // '<<'
     GreaterGreater,     // '>>'
     Dot,                // '.'
+    DotDot,             // '..'
     Comment,            // '//'
     EndOfFile,          // 'EOF'
     NewLine,            // 'NEWLINE'
     Int,                // 'int'
     Float,              // 'float'
     Char,               // 'char'
+    Bool,               // 'bool'
     String,             // 'string'
     Var,                // 'var'
     Struct,             // 'struct'
+    Enum,               // 'enum'
+    Match,              // 'match'
     If,                 // 'if'
     Else,               // 'else'
     Func,               // 'func'
     Loop,               // 'loop'
+    For,                // 'for'
+    In,                 // 'in'
     Break,              // 'break'
+    Continue,           // 'continue'
     Return,             // 'return'
     Exit,               // 'exit'
     Panic,              // 'panic'
@@ -78,10 +88,14 @@ impl fmt::Display for Token {
             Self::Ampersand => "&",
             Self::AmpersandAmpersand => "&&",
             Self::Bang => "!",
+            Self::Caret => "^",
+            Self::Question => "?",
             Self::Colon => ":",
+            Self::ColonColon => "::",
             Self::Comma => ",",
             Self::Comment => "//",
             Self::Dot => ".",
+            Self::DotDot => "..",
             Self::If => "if",
             Self::Else => "else",
             Self::NewLine => "NEWLINE",
@@ -103,6 +117,8 @@ impl fmt::Display for Token {
             Self::LessEqualFloat => "<=.",
             Self::Var => "var",
             Self::Struct => "struct",
+            Self::Enum => "enum",
+            Self::Match => "match",
             Self::Concat => "<>",
             Self::LessLess => "<<",
             Self::Minus => "-",
@@ -112,7 +128,10 @@ impl fmt::Display for Token {
             Self::Exit => "exit",
             Self::Return => "return",
             Self::Loop => "loop",
+            Self::For => "for",
+            Self::In => "in",
             Self::Break => "break",
+            Self::Continue => "continue",
             Self::Percent => "%",
             Self::Plus => "+",
             Self::PlusFloat => "+.",
@@ -129,6 +148,7 @@ impl fmt::Display for Token {
             Self::Int => "int",
             Self::Float => "float",
             Self::Char => "char",
+            Self::Bool => "bool",
             Self::String => "string",
         };
 
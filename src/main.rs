@@ -1,4 +1,4 @@
-use awa::cli;
+use awa::{cli, driver};
 use camino::Utf8PathBuf;
 use clap::{
     builder::{styling::AnsiColor, Styles},
@@ -30,11 +30,35 @@ enum Command {
 
     /// Run the specified file in interactive environment.
     ///
+    /// Without a terminal on stdin, or with `--batch`, runs the program
+    /// straight through, without menu.
+    /// With `--watch`, hotswaps functions and structs, changed in the saved file.
     /// By default, runs `main.awa`
     Run {
         filename: Option<Utf8PathBuf>,
         #[arg(long)]
         unoptimized: bool,
+        /// Print values of local variables in backtraces
+        #[arg(long)]
+        backtrace_locals: bool,
+        /// Roll heap objects back along with the stack on backoff
+        #[arg(long)]
+        heap_snapshots: bool,
+        /// Maximum amount of nested calls [default: 1000000]
+        #[arg(long)]
+        max_call_depth: Option<usize>,
+        /// Stop the program after running this amount of instructions
+        #[arg(long)]
+        max_instructions: Option<u64>,
+        /// Stop the program, when live objects do not fit into this amount
+        #[arg(long)]
+        max_heap_objects: Option<usize>,
+        /// Run without interactive menu, as done anyway when stdin is not a terminal
+        #[arg(long)]
+        batch: bool,
+        /// Hotswap functions and structs, changed in the source file, each time it is saved
+        #[arg(long)]
+        watch: bool,
     },
 }
 
@@ -46,6 +70,25 @@ fn main() {
         Command::Run {
             filename,
             unoptimized,
-        } => cli::run::handle(filename, unoptimized),
+            backtrace_locals,
+            heap_snapshots,
+            max_call_depth,
+            max_instructions,
+            max_heap_objects,
+            batch,
+            watch,
+        } => std::process::exit(cli::run::handle(
+            filename,
+            driver::RunOptions {
+                unoptimized,
+                backtrace_locals,
+                heap_snapshots,
+                max_call_depth,
+                max_instructions,
+                max_heap_objects,
+                watch_interval: watch.then_some(driver::DEFAULT_WATCH_INTERVAL),
+            },
+            batch,
+        )),
     }
 }
@@ -2,13 +2,18 @@ use std::collections::HashMap;
 
 use ecow::EcoString;
 
-use crate::vm::instruction::{Bytecode, Instruction};
+use crate::vm::{
+    instruction::{Bytecode, Instruction},
+    CodeLocation,
+};
 
 pub struct Optimizer {
     /// for function optimization is function body
     ///
     /// for cycle optimization is the whole function
     bytecode: Bytecode,
+    /// Location of each instruction of `bytecode`, kept in sync with it
+    locations: Vec<Option<CodeLocation>>,
     /// for function optimization is `None`
     ///
     /// for cycle optimization is `Some((cycle_start, cycle_end))`
@@ -18,9 +23,14 @@ pub struct Optimizer {
 
 impl Optimizer {
     #[must_use]
-    pub fn optimize_function(function_body: Bytecode, shift: usize) -> Bytecode {
+    pub fn optimize_function(
+        function_body: Bytecode,
+        locations: Vec<Option<CodeLocation>>,
+        shift: usize,
+    ) -> (Bytecode, Vec<Option<CodeLocation>>) {
         let mut optimizer = Self {
             bytecode: function_body,
+            locations,
             hot_region: None,
             shift,
         };
@@ -31,12 +41,14 @@ impl Optimizer {
     #[must_use]
     pub fn optimize_loop(
         function_code: Bytecode,
+        locations: Vec<Option<CodeLocation>>,
         loop_start: usize,
         loop_end: usize,
         shift: usize,
-    ) -> Bytecode {
+    ) -> (Bytecode, Vec<Option<CodeLocation>>) {
         let mut optimizer = Self {
             bytecode: function_code,
+            locations,
             hot_region: Some((loop_start, loop_end)),
             shift,
         };
@@ -44,7 +56,7 @@ impl Optimizer {
         optimizer.optimize()
     }
 
-    fn optimize(&mut self) -> Bytecode {
+    fn optimize(&mut self) -> (Bytecode, Vec<Option<CodeLocation>>) {
         let initial_length = self.bytecode.len();
 
         let mut made_changes = true;
@@ -66,9 +78,12 @@ impl Optimizer {
 
                 let new_start = start.min(new_end);
 
-                self.bytecode[new_start..=new_end].to_vec()
+                (
+                    self.bytecode[new_start..=new_end].to_vec(),
+                    self.locations[new_start..=new_end].to_vec(),
+                )
             }
-            None => self.bytecode.clone(),
+            None => (self.bytecode.clone(), self.locations.clone()),
         }
     }
 
@@ -101,6 +116,10 @@ impl Optimizer {
                         constants.push((string.clone().to_string(), "string"));
                         last_type = Some("string");
                     }
+                    Instruction::PushBool(bool) => {
+                        constants.push((bool.to_string(), "bool"));
+                        last_type = Some("bool");
+                    }
                     Instruction::Call(_)
                     | Instruction::GetField(_)
                     | Instruction::GetByIndex
@@ -115,27 +134,50 @@ impl Optimizer {
                     | Instruction::SubInt
                     | Instruction::MulInt
                     | Instruction::DivInt
+                    | Instruction::WrappingAddInt
+                    | Instruction::WrappingSubInt
+                    | Instruction::WrappingMulInt
+                    | Instruction::SaturatingAddInt
+                    | Instruction::SaturatingSubInt
+                    | Instruction::SaturatingMulInt
+                    | Instruction::BitwiseAnd
+                    | Instruction::BitwiseOr
+                    | Instruction::BitwiseXor
+                    | Instruction::ShiftLeft
+                    | Instruction::ShiftRight
                         if last_type == Some("int") && constants.len() >= 2 =>
                     {
                         let rhs = constants.pop().unwrap().0.parse::<i64>().unwrap();
                         let lhs = constants.pop().unwrap().0.parse::<i64>().unwrap();
 
                         let result = match &self.bytecode[j] {
-                            Instruction::AddInt => lhs + rhs,
-                            Instruction::SubInt => lhs - rhs,
-                            Instruction::MulInt => lhs * rhs,
-                            Instruction::DivInt => {
-                                if rhs == 0 {
-                                    can_fold = false;
-                                    break;
-                                }
-
-                                lhs / rhs
+                            Instruction::AddInt => lhs.checked_add(rhs),
+                            Instruction::SubInt => lhs.checked_sub(rhs),
+                            Instruction::MulInt => lhs.checked_mul(rhs),
+                            Instruction::DivInt => lhs.checked_div(rhs),
+                            Instruction::WrappingAddInt => Some(lhs.wrapping_add(rhs)),
+                            Instruction::WrappingSubInt => Some(lhs.wrapping_sub(rhs)),
+                            Instruction::WrappingMulInt => Some(lhs.wrapping_mul(rhs)),
+                            Instruction::SaturatingAddInt => Some(lhs.saturating_add(rhs)),
+                            Instruction::SaturatingSubInt => Some(lhs.saturating_sub(rhs)),
+                            Instruction::SaturatingMulInt => Some(lhs.saturating_mul(rhs)),
+                            Instruction::BitwiseAnd => Some(lhs & rhs),
+                            Instruction::BitwiseOr => Some(lhs | rhs),
+                            Instruction::BitwiseXor => Some(lhs ^ rhs),
+                            Instruction::ShiftLeft => {
+                                u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs))
                             }
-                            _ => {
-                                can_fold = false;
-                                break;
+                            Instruction::ShiftRight => {
+                                u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs))
                             }
+                            _ => None,
+                        };
+
+                        // NOTE: overflow, division by zero and out of range shifts
+                        // are left for VM to report
+                        let Some(result) = result else {
+                            can_fold = false;
+                            break;
                         };
 
                         constants.push((result.to_string(), "int"));
@@ -169,6 +211,41 @@ impl Optimizer {
 
                         constants.push((result.to_string(), "float"));
                     }
+                    Instruction::NegateInt if last_type == Some("int") && !constants.is_empty() => {
+                        let operand = constants.pop().unwrap().0.parse::<i64>().unwrap();
+
+                        let Some(negated) = operand.checked_neg() else {
+                            can_fold = false;
+                            break;
+                        };
+
+                        constants.push((negated.to_string(), "int"));
+                    }
+                    Instruction::And | Instruction::Or
+                        if last_type == Some("bool") && constants.len() >= 2 =>
+                    {
+                        let rhs = constants.pop().unwrap().0.parse::<bool>().unwrap();
+                        let lhs = constants.pop().unwrap().0.parse::<bool>().unwrap();
+
+                        let result = match &self.bytecode[j] {
+                            Instruction::And => lhs && rhs,
+                            _ => lhs || rhs,
+                        };
+
+                        constants.push((result.to_string(), "bool"));
+                    }
+                    Instruction::Not if last_type == Some("bool") && !constants.is_empty() => {
+                        let operand = constants.pop().unwrap().0.parse::<bool>().unwrap();
+
+                        constants.push(((!operand).to_string(), "bool"));
+                    }
+                    Instruction::NegateFloat
+                        if last_type == Some("float") && !constants.is_empty() =>
+                    {
+                        let operand = constants.pop().unwrap().0.parse::<f64>().unwrap();
+
+                        constants.push(((-operand).to_string(), "float"));
+                    }
                     Instruction::Concat if last_type == Some("string") && constants.len() >= 2 => {
                         let rhs = constants.pop().unwrap().0;
                         let lhs = constants.pop().unwrap().0;
@@ -185,21 +262,32 @@ impl Optimizer {
             }
 
             if can_fold && !constants.is_empty() && j > i {
+                // NOTE: folded constants are attributed to the last folded operation
+                let location = self.locations[j - 1];
+
                 self.bytecode.drain(i..j);
+                self.locations.drain(i..j);
+
+                // NOTE: constants that were not consumed by any operation
+                // are still needed by the following instructions
+                for (folded_value, value_type) in constants {
+                    if value_type == "int" {
+                        self.bytecode
+                            .insert(i, Instruction::PushInt(folded_value.parse().unwrap()));
+                    } else if value_type == "float" {
+                        self.bytecode
+                            .insert(i, Instruction::PushFloat(folded_value.parse().unwrap()));
+                    } else if value_type == "string" {
+                        self.bytecode
+                            .insert(i, Instruction::PushString(folded_value.into()));
+                    } else if value_type == "bool" {
+                        self.bytecode
+                            .insert(i, Instruction::PushBool(folded_value.parse().unwrap()));
+                    }
+                    self.locations.insert(i, location);
 
-                let (folded_value, value_type) = constants.pop().unwrap();
-                if value_type == "int" {
-                    self.bytecode
-                        .insert(i, Instruction::PushInt(folded_value.parse().unwrap()));
-                } else if value_type == "float" {
-                    self.bytecode
-                        .insert(i, Instruction::PushFloat(folded_value.parse().unwrap()));
-                } else if value_type == "string" {
-                    self.bytecode
-                        .insert(i, Instruction::PushString(folded_value.into()));
+                    i += 1;
                 }
-
-                i += 1;
             } else {
                 i += 1;
             }
@@ -218,17 +306,23 @@ impl Optimizer {
                     ) if first_variable == second_variable => {
                         self.bytecode.remove(i);
                         self.bytecode.remove(i);
+                        self.locations.remove(i);
+                        self.locations.remove(i);
                         continue;
                     }
                     (
                         Instruction::PushInt(_)
                         | Instruction::PushFloat(_)
                         | Instruction::PushString(_)
-                        | Instruction::PushChar(_),
+                        | Instruction::PushChar(_)
+                        | Instruction::PushBool(_)
+                        | Instruction::PushNil,
                         Instruction::Pop,
                     ) => {
                         self.bytecode.remove(i);
                         self.bytecode.remove(i);
+                        self.locations.remove(i);
+                        self.locations.remove(i);
                         continue;
                     }
                     _ => {}
@@ -270,9 +364,12 @@ impl Optimizer {
                             | Instruction::PushFloat(_)
                             | Instruction::PushString(_)
                             | Instruction::PushChar(_)
+                            | Instruction::PushBool(_)
+                            | Instruction::PushNil
                             | Instruction::PushArray(_)
                             | Instruction::LoadToStack(_)
-                            | Instruction::NewStruct(_) => {
+                            | Instruction::NewStruct(_)
+                            | Instruction::NewEnum(_, _, 0) => {
                                 stack_balance += 1;
                                 if stack_balance != 0 {
                                     start -= 1;
@@ -285,6 +382,17 @@ impl Optimizer {
                             | Instruction::MulInt
                             | Instruction::DivInt
                             | Instruction::Mod
+                            | Instruction::WrappingAddInt
+                            | Instruction::WrappingSubInt
+                            | Instruction::WrappingMulInt
+                            | Instruction::SaturatingAddInt
+                            | Instruction::SaturatingSubInt
+                            | Instruction::SaturatingMulInt
+                            | Instruction::BitwiseAnd
+                            | Instruction::BitwiseOr
+                            | Instruction::BitwiseXor
+                            | Instruction::ShiftLeft
+                            | Instruction::ShiftRight
                             | Instruction::AddFloat
                             | Instruction::SubFloat
                             | Instruction::MulFloat
@@ -306,7 +414,13 @@ impl Optimizer {
                                 stack_balance -= 1;
                                 start -= 1;
                             }
-                            Instruction::GetField(_) => {
+                            Instruction::GetField(_)
+                            | Instruction::IsVariant(_)
+                            | Instruction::GetVariantField(_)
+                            | Instruction::NegateInt
+                            | Instruction::NegateFloat
+                            | Instruction::Not
+                            | Instruction::Length => {
                                 start -= 1;
                             }
                             _ => break,
@@ -326,6 +440,7 @@ impl Optimizer {
                         }
 
                         self.bytecode.drain(start - 1..=end);
+                        self.locations.drain(start - 1..=end);
                     }
                 }
             }
@@ -389,9 +504,12 @@ impl Optimizer {
                                 | Instruction::PushFloat(_)
                                 | Instruction::PushString(_)
                                 | Instruction::PushChar(_)
+                                | Instruction::PushBool(_)
+                                | Instruction::PushNil
                                 | Instruction::PushArray(_)
                                 | Instruction::LoadToStack(_)
-                                | Instruction::NewStruct(_) => {
+                                | Instruction::NewStruct(_)
+                                | Instruction::NewEnum(_, _, 0) => {
                                     stack_balance += 1;
 
                                     if stack_balance != 0 {
@@ -405,6 +523,17 @@ impl Optimizer {
                                 | Instruction::MulInt
                                 | Instruction::DivInt
                                 | Instruction::Mod
+                                | Instruction::WrappingAddInt
+                                | Instruction::WrappingSubInt
+                                | Instruction::WrappingMulInt
+                                | Instruction::SaturatingAddInt
+                                | Instruction::SaturatingSubInt
+                                | Instruction::SaturatingMulInt
+                                | Instruction::BitwiseAnd
+                                | Instruction::BitwiseOr
+                                | Instruction::BitwiseXor
+                                | Instruction::ShiftLeft
+                                | Instruction::ShiftRight
                                 | Instruction::AddFloat
                                 | Instruction::SubFloat
                                 | Instruction::MulFloat
@@ -428,7 +557,13 @@ impl Optimizer {
                                     stack_balance -= 1;
                                     start -= 1;
                                 }
-                                Instruction::GetField(_) => {
+                                Instruction::GetField(_)
+                                | Instruction::IsVariant(_)
+                                | Instruction::GetVariantField(_)
+                                | Instruction::NegateInt
+                                | Instruction::NegateFloat
+                                | Instruction::Not
+                                | Instruction::Length => {
                                     start -= 1;
                                 }
                                 _ => break,
@@ -451,6 +586,7 @@ impl Optimizer {
                             }
 
                             self.bytecode.drain(start - 1..=position);
+                            self.locations.drain(start - 1..=position);
                         }
                     }
 
@@ -468,45 +604,45 @@ impl Optimizer {
         while i < self.bytecode.len() {
             match &self.bytecode[i] {
                 Instruction::LoadToStack(load_variable_name)
-                    if load_variable_name == variable_name =>
+                    if load_variable_name == variable_name && i > 0 =>
                 {
-                    if i > 0 {
-                        let mut j = i + 1;
-                        let mut is_assignment = false;
+                    let mut j = i + 1;
+                    let mut is_assignment = false;
 
-                        while j < self.bytecode.len() {
-                            match &self.bytecode[j] {
-                                Instruction::PushInt(_)
-                                | Instruction::PushFloat(_)
-                                | Instruction::PushString(_)
-                                | Instruction::PushChar(_)
-                                | Instruction::LoadToStack(_)
-                                | Instruction::AddInt
-                                | Instruction::SubInt
-                                | Instruction::MulInt
-                                | Instruction::DivInt
-                                | Instruction::GreaterInt
-                                | Instruction::GreaterEqualInt => {
-                                    j += 1;
-                                    continue;
-                                }
-                                Instruction::JumpIfFalse(_) => {
-                                    is_assignment = false;
-                                    break;
-                                }
-                                Instruction::StoreInMap(store_variable_name)
-                                    if store_variable_name == variable_name =>
-                                {
-                                    is_assignment = true;
-                                    break;
-                                }
-                                _ => break,
+                    while j < self.bytecode.len() {
+                        match &self.bytecode[j] {
+                            Instruction::PushInt(_)
+                            | Instruction::PushFloat(_)
+                            | Instruction::PushString(_)
+                            | Instruction::PushChar(_)
+                            | Instruction::PushBool(_)
+                            | Instruction::PushNil
+                            | Instruction::LoadToStack(_)
+                            | Instruction::AddInt
+                            | Instruction::SubInt
+                            | Instruction::MulInt
+                            | Instruction::DivInt
+                            | Instruction::NegateInt
+                            | Instruction::GreaterInt
+                            | Instruction::GreaterEqualInt => {
+                                j += 1;
                             }
+                            Instruction::JumpIfFalse(_) => {
+                                is_assignment = false;
+                                break;
+                            }
+                            Instruction::StoreInMap(store_variable_name)
+                                if store_variable_name == variable_name =>
+                            {
+                                is_assignment = true;
+                                break;
+                            }
+                            _ => break,
                         }
+                    }
 
-                        if !is_assignment {
-                            return true;
-                        }
+                    if !is_assignment {
+                        return true;
                     }
                 }
                 _ => {}
@@ -8,7 +8,7 @@ use error::{ParsingError, Type::MissingRightOperand};
 use itertools::{peek_nth, PeekNth};
 use vec1::Vec1;
 
-use crate::ast::definition::StructField;
+use crate::ast::definition::{EnumVariant, StructField};
 use crate::ast::expression::StructFieldValue;
 use crate::ast::location::Location;
 use crate::ast::reassignment::{UntypedReassignment, UntypedReassignmentTarget};
@@ -17,9 +17,11 @@ use crate::{
         argument,
         assignment::UntypedAssignment,
         definition, expression,
+        iterable::UntypedIterable,
         location::Location as AstLocation,
         module,
-        operator::BinaryOperator,
+        operator::{BinaryOperator, UnaryOperator},
+        pattern::{UntypedMatchArm, UntypedPattern, WILDCARD},
         statement::{self, UntypedStatement},
     },
     lex::{
@@ -33,8 +35,8 @@ use crate::{
 
 /// Parses the input string into untyped AST module.
 ///
-/// - Modules contain struct and function definitions
-/// - Function definitions contain statements (if-elses, loops, breaks, assignments,
+/// - Modules contain struct, enum and function definitions
+/// - Function definitions contain statements (if-elses, matches, loops, breaks, assignments,
 ///   standalone expressions, todos, panics, exits)
 /// - Statements consist of expressions (primitive type literals, value accesses and
 ///   initializations)
@@ -83,6 +85,8 @@ pub struct Parser<T: Iterator<Item = LexResult>> {
     input_tokens: PeekNth<T>,
     lexical_errors: Vec<LexicalError>,
     current_token: Option<TokenSpan>,
+    /// `for` header is followed by `{`, so `name {` there is not a struct initialization
+    is_struct_initialization_allowed: bool,
 }
 
 impl<T: Iterator<Item = LexResult>> Parser<T> {
@@ -91,6 +95,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
             input_tokens: tokens,
             lexical_errors: vec![],
             current_token: None,
+            is_struct_initialization_allowed: true,
         };
 
         let _ = parser.advance_token();
@@ -156,6 +161,10 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                     Ok(definition) => Ok(Some(definition)),
                     Err(parsing_error) => Err(parsing_error),
                 },
+                Token::Enum => match self.parse_enum_definition() {
+                    Ok(definition) => Ok(Some(definition)),
+                    Err(parsing_error) => Err(parsing_error),
+                },
                 Token::Func => match self.parse_function_definition() {
                     Ok(definition) => Ok(Some(definition)),
                     Err(parsing_error) => Err(parsing_error),
@@ -163,7 +172,9 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                 token => Err(ParsingError {
                     error: error::Type::UnexpectedToken {
                         token,
-                        expected: "either function or struct definition".to_string().into(),
+                        expected: "either function, struct or enum definition"
+                            .to_string()
+                            .into(),
                     },
                     location: LexLocation {
                         start: token_span.start,
@@ -184,26 +195,11 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
         loop {
             if let Some(token_span) = self.current_token.clone() {
                 if token_span.token == Token::LeftParenthesis {
-                    let _ = self.advance_token();
-
-                    match self.parse_expression()? {
-                        Some(inner_expression) => {
-                            let _ = self.expect_token(&Token::RightParenthesis)?;
-                            expression_stack.push(inner_expression);
-                        }
-                        None => {
-                            return Err(ParsingError {
-                                error: error::Type::UnexpectedToken {
-                                    token: token_span.token,
-                                    expected: "expression inside parentheses".to_string().into(),
-                                },
-                                location: LexLocation {
-                                    start: token_span.start,
-                                    end: token_span.end,
-                                },
-                            });
-                        }
-                    }
+                    let inner_expression = self.parse_parenthesized_expression(token_span)?;
+                    expression_stack.push(inner_expression);
+                } else if let Some(operator) = token_to_unary_operator(&token_span.token) {
+                    let unary_expression = self.parse_unary_operation(&token_span, operator)?;
+                    expression_stack.push(unary_expression);
                 } else {
                     match self.parse_expression_unit()? {
                         Some(unit) => expression_stack.push(unit),
@@ -251,15 +247,102 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
         ))
     }
 
+    fn parse_parenthesized_expression(
+        &mut self,
+        token_span: TokenSpan,
+    ) -> Result<expression::UntypedExpression, ParsingError> {
+        let _ = self.advance_token();
+
+        match self.parse_expression()? {
+            Some(inner_expression) => {
+                let _ = self.expect_token(&Token::RightParenthesis)?;
+                Ok(inner_expression)
+            }
+            None => Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: token_span.token,
+                    expected: "expression inside parentheses".to_string().into(),
+                },
+                location: LexLocation {
+                    start: token_span.start,
+                    end: token_span.end,
+                },
+            }),
+        }
+    }
+
+    // NOTE: unary operators bind tighter than any binary operator,
+    // so the operand is a single unit, a parenthesized expression
+    // or another unary operation (`!!a`, `-(a + b)`, `-.x`)
+    fn parse_unary_operation(
+        &mut self,
+        operator_token_span: &TokenSpan,
+        operator: UnaryOperator,
+    ) -> Result<expression::UntypedExpression, ParsingError> {
+        let _ = self.advance_token();
+
+        let operand = match self.current_token.clone() {
+            Some(token_span) if token_span.token == Token::LeftParenthesis => {
+                Some(self.parse_parenthesized_expression(token_span)?)
+            }
+            Some(token_span) => match token_to_unary_operator(&token_span.token) {
+                Some(inner_operator) => {
+                    Some(self.parse_unary_operation(&token_span, inner_operator)?)
+                }
+                None => self.parse_expression_unit()?,
+            },
+            None => None,
+        };
+
+        let Some(operand) = operand else {
+            return Err(ParsingError {
+                error: MissingRightOperand,
+                location: LexLocation {
+                    start: operator_token_span.start,
+                    end: operator_token_span.end,
+                },
+            });
+        };
+
+        Ok(expression::UntypedExpression::UnaryOperation {
+            location: AstLocation {
+                start: operator_token_span.start,
+                end: operand.get_location().end,
+            },
+            operator,
+            operand: Box::new(operand),
+        })
+    }
+
     fn parse_expression_unit(
         &mut self,
     ) -> Result<Option<expression::UntypedExpression>, ParsingError> {
         match self.current_token.clone() {
             Some(token_span) => match token_span.token {
+                Token::Name { value } if value == "nil" => {
+                    let _ = self.advance_token();
+                    Ok(Some(expression::UntypedExpression::NilLiteral {
+                        location: AstLocation {
+                            start: token_span.start,
+                            end: token_span.end,
+                        },
+                    }))
+                }
+                Token::Name { value } if matches!(value.as_str(), "true" | "false") => {
+                    let _ = self.advance_token();
+                    Ok(Some(expression::UntypedExpression::BooleanLiteral {
+                        location: AstLocation {
+                            start: token_span.start,
+                            end: token_span.end,
+                        },
+                        value: value == "true",
+                    }))
+                }
                 // NOTE: name can be either:
                 // - variable value access (varName)
                 // - function call (`funcName()` or `funcName(argFirst, argSecond)`)
                 // - struct field access (`structName.fieldName`)
+                // - enum variant initialization (`enumName::variantName(value)`)
                 // - array value access (`arrayName[indexVariable]` or `arrayName[1]`)
                 Token::Name { value: sth_name } => {
                     let start_location = token_span.start;
@@ -270,6 +353,9 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
 
                                 self.parse_function_call(&sth_name, start_location)?
                             }
+                            Token::ColonColon => {
+                                self.parse_enum_initialization(sth_name, start_location)?
+                            }
                             Token::Dot => {
                                 let _ = self.advance_token();
                                 let _ = self.advance_token();
@@ -325,7 +411,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                                     index_expression: Box::new(index_value),
                                 }
                             }
-                            Token::LeftBrace => {
+                            Token::LeftBrace if self.is_struct_initialization_allowed => {
                                 let _ = self.advance_token();
                                 let _ = self.advance_token();
                                 let (fields, right_brace_span) = if let Some(token_span) =
@@ -434,10 +520,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                     let elements =
                         self.parse_series(&Self::parse_expression, Some(&Token::Comma))?;
 
-                    let elements = match Vec1::try_from_vec(elements) {
-                        Ok(elements) => Some(elements),
-                        Err(_) => None,
-                    };
+                    let elements = Vec1::try_from_vec(elements).ok();
 
                     let right_brace_span = self.expect_token(&Token::RightBrace)?;
 
@@ -467,18 +550,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
         function_name: &EcoString,
         start_location: u32,
     ) -> Result<expression::UntypedExpression, ParsingError> {
-        let _ = self.expect_token(&Token::LeftParenthesis)?;
-
-        let (call_arguments, right_parenthesis_token_span) =
-            if let Some(token_span) = self.maybe_token(&Token::RightParenthesis) {
-                (None, token_span)
-            } else {
-                let call_args =
-                    self.parse_series(&Self::parse_function_call_argument, Some(&Token::Comma))?;
-                let args = Some(Vec1::try_from_vec(call_args).unwrap());
-                let span = self.expect_token(&Token::RightParenthesis)?;
-                (args, span)
-            };
+        let (call_arguments, right_parenthesis_token_span) = self.parse_call_arguments()?;
 
         Ok(expression::UntypedExpression::FunctionCall {
             location: AstLocation {
@@ -490,6 +562,72 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
         })
     }
 
+    fn parse_call_arguments(
+        &mut self,
+    ) -> Result<(Option<Vec1<argument::CallArgumentUntyped>>, TokenSpan), ParsingError> {
+        let _ = self.expect_token(&Token::LeftParenthesis)?;
+
+        if let Some(token_span) = self.maybe_token(&Token::RightParenthesis) {
+            return Ok((None, token_span));
+        }
+
+        let call_args =
+            self.parse_series(&Self::parse_function_call_argument, Some(&Token::Comma))?;
+        let args = Some(Vec1::try_from_vec(call_args).unwrap());
+        let span = self.expect_token(&Token::RightParenthesis)?;
+
+        Ok((args, span))
+    }
+
+    // NOTE: enum variant is constructed with `enumName::variantName(firstValue, secondValue)`,
+    // parentheses are omitted for variants without payload (`enumName::variantName`)
+    fn parse_enum_initialization(
+        &mut self,
+        enum_name: EcoString,
+        start_location: u32,
+    ) -> Result<expression::UntypedExpression, ParsingError> {
+        let _ = self.advance_token();
+        let _ = self.advance_token();
+        let variant_token_span = self.advance_token().ok_or(ParsingError {
+            error: error::Type::UnexpectedEof,
+            location: LexLocation { start: 0, end: 0 },
+        })?;
+
+        let Token::Name {
+            value: variant_name,
+        } = variant_token_span.token
+        else {
+            return Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: variant_token_span.token,
+                    expected: "enum variant name".to_string().into(),
+                },
+                location: LexLocation {
+                    start: variant_token_span.start,
+                    end: variant_token_span.end,
+                },
+            });
+        };
+
+        let (arguments, end) = match self.current_token.as_ref() {
+            Some(token_span) if token_span.token == Token::LeftParenthesis => {
+                let (arguments, right_parenthesis_token_span) = self.parse_call_arguments()?;
+                (arguments, right_parenthesis_token_span.end)
+            }
+            _ => (None, variant_token_span.end),
+        };
+
+        Ok(expression::UntypedExpression::EnumInitialization {
+            location: AstLocation {
+                start: start_location,
+                end,
+            },
+            enum_name,
+            variant_name,
+            arguments,
+        })
+    }
+
     fn parse_struct_defenition(&mut self) -> Result<definition::DefinitionUntyped, ParsingError> {
         let _ = self.advance_token();
         let name_token_span = self.advance_token().ok_or_else(|| ParsingError {
@@ -519,10 +657,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
             location: LexLocation { start: 0, end: 0 },
         })?;
 
-        let fields = match Vec1::try_from_vec(fields) {
-            Ok(fields) => Some(fields),
-            Err(_) => None,
-        };
+        let fields = Vec1::try_from_vec(fields).ok();
 
         Ok(definition::DefinitionUntyped::Struct {
             location: AstLocation {
@@ -534,6 +669,70 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
         })
     }
 
+    fn parse_enum_definition(&mut self) -> Result<definition::DefinitionUntyped, ParsingError> {
+        let _ = self.advance_token();
+        let name_token_span = self.advance_token().ok_or_else(|| ParsingError {
+            error: error::Type::UnexpectedEof,
+            location: LexLocation { start: 0, end: 0 },
+        })?;
+
+        let Token::Name { value: name } = name_token_span.token else {
+            return Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: name_token_span.token,
+                    expected: "enum name".to_string().into(),
+                },
+                location: LexLocation {
+                    start: name_token_span.start,
+                    end: name_token_span.end,
+                },
+            });
+        };
+
+        let _ = self.expect_token(&Token::LeftBrace)?;
+
+        let variants = self.parse_series(&Self::parse_enum_variant, None)?;
+
+        let right_brace_token_span = self.expect_token(&Token::RightBrace)?;
+
+        let variants = Vec1::try_from_vec(variants).ok();
+
+        Ok(definition::DefinitionUntyped::Enum {
+            location: AstLocation {
+                start: name_token_span.start,
+                end: right_brace_token_span.end,
+            },
+            name,
+            variants,
+        })
+    }
+
+    // NOTE: variant payload is declared the same way as struct fields,
+    // but in parentheses and separated by commas (`circle(radius float)`)
+    fn parse_enum_variant(&mut self) -> Result<Option<EnumVariant>, ParsingError> {
+        let Some(TokenSpan {
+            token: Token::Name { value: name },
+            ..
+        }) = self.current_token.clone()
+        else {
+            return Ok(None);
+        };
+
+        let _ = self.advance_token();
+
+        if self.maybe_token(&Token::LeftParenthesis).is_none() {
+            return Ok(Some(EnumVariant { name, fields: None }));
+        }
+
+        let fields = self.parse_series(&Self::parse_struct_field, Some(&Token::Comma))?;
+        let _ = self.expect_token(&Token::RightParenthesis)?;
+
+        Ok(Some(EnumVariant {
+            name,
+            fields: Vec1::try_from_vec(fields).ok(),
+        }))
+    }
+
     fn parse_struct_field_value(&mut self) -> Result<Option<StructFieldValue>, ParsingError> {
         let name_token_span = self.advance_token().ok_or_else(|| ParsingError {
             error: error::Type::UnexpectedEof,
@@ -794,6 +993,29 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                 Token::Name { .. } => Ok(Some(self.parse_reassignment(token_span)?)),
                 Token::Loop => {
                     let _ = self.advance_token();
+
+                    let condition = if let Some(left_parenthesis_token_span) =
+                        self.maybe_token(&Token::LeftParenthesis)
+                    {
+                        let Some(condition) = self.parse_expression()? else {
+                            return Err(ParsingError {
+                                error: error::Type::UnexpectedToken {
+                                    token: left_parenthesis_token_span.token,
+                                    expected: "loop condition expression".to_string().into(),
+                                },
+                                location: LexLocation {
+                                    start: left_parenthesis_token_span.start,
+                                    end: left_parenthesis_token_span.end,
+                                },
+                            });
+                        };
+                        let _ = self.expect_token(&Token::RightParenthesis)?;
+
+                        Some(Box::new(condition))
+                    } else {
+                        None
+                    };
+
                     let _ = self.expect_token(&Token::LeftBrace)?;
 
                     let body = self
@@ -804,6 +1026,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                     let end = right_brace_token_span.end;
 
                     Ok(Some(UntypedStatement::Loop {
+                        condition,
                         body,
                         location: Location {
                             start: token_span.start,
@@ -811,48 +1034,9 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                         },
                     }))
                 }
-                Token::If => {
-                    let _ = self.advance_token();
-                    let _ = self.expect_token(&Token::LeftParenthesis)?;
-                    let condition = self.parse_expression()?;
-                    let _ = self.expect_token(&Token::RightParenthesis)?;
-
-                    let _ = self.expect_token(&Token::LeftBrace)?;
-                    let if_body = self
-                        .parse_statement_sequence()?
-                        .map(|(statements, _)| statements);
-
-                    let right_brace_token_span = self.expect_token(&Token::RightBrace)?;
-                    let mut end = right_brace_token_span.end;
-
-                    let else_body = if let Some(Token::Else) =
-                        self.current_token.as_ref().map(|tok| &tok.token)
-                    {
-                        self.advance_token();
-                        let _ = self.expect_token(&Token::LeftBrace)?;
-
-                        let else_statements = self
-                            .parse_statement_sequence()?
-                            .map(|(statements, _)| statements);
-
-                        let else_right_brace = self.expect_token(&Token::RightBrace)?;
-                        end = else_right_brace.end;
-
-                        else_statements
-                    } else {
-                        None
-                    };
-
-                    Ok(Some(UntypedStatement::If {
-                        condition: Box::new(condition.unwrap()),
-                        if_body,
-                        else_body,
-                        location: Location {
-                            start: token_span.start,
-                            end,
-                        },
-                    }))
-                }
+                Token::For => self.parse_for_statement(&token_span).map(Some),
+                Token::If => self.parse_if_statement(&token_span).map(Some),
+                Token::Match => self.parse_match_statement(&token_span).map(Some),
                 Token::Return => {
                     let _ = self.advance_token();
                     let mut value = None;
@@ -889,22 +1073,37 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                         },
                     }))
                 }
+                Token::Continue => {
+                    let _ = self.advance_token();
+                    Ok(Some(UntypedStatement::Continue {
+                        location: Location {
+                            start: token_span.start,
+                            end: token_span.end,
+                        },
+                    }))
+                }
                 Token::Panic => {
                     let _ = self.advance_token();
+                    let (message, end) = self.parse_statement_argument(&token_span)?;
+
                     Ok(Some(UntypedStatement::Panic {
                         location: Location {
                             start: token_span.start,
-                            end: token_span.end,
+                            end,
                         },
+                        message: Box::new(message),
                     }))
                 }
                 Token::Exit => {
                     let _ = self.advance_token();
+                    let (code, end) = self.parse_statement_argument(&token_span)?;
+
                     Ok(Some(UntypedStatement::Exit {
                         location: Location {
                             start: token_span.start,
-                            end: token_span.end,
+                            end,
                         },
+                        code: Box::new(code),
                     }))
                 }
                 _ => {
@@ -917,6 +1116,317 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
         }
     }
 
+    // NOTE: `for` iterates either over half-open int range (`for i in 0..n`)
+    // or over array elements (`for element in array`)
+    fn parse_for_statement(
+        &mut self,
+        for_token_span: &TokenSpan,
+    ) -> Result<UntypedStatement, ParsingError> {
+        let _ = self.advance_token();
+
+        let variable_token_span = self.advance_token().ok_or(ParsingError {
+            error: error::Type::UnexpectedEof,
+            location: LexLocation {
+                start: for_token_span.start,
+                end: for_token_span.end,
+            },
+        })?;
+
+        let Token::Name {
+            value: variable_name,
+        } = variable_token_span.token
+        else {
+            return Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: variable_token_span.token,
+                    expected: "loop variable name".to_string().into(),
+                },
+                location: LexLocation {
+                    start: variable_token_span.start,
+                    end: variable_token_span.end,
+                },
+            });
+        };
+
+        let in_token_span = self.expect_token(&Token::In)?;
+
+        self.is_struct_initialization_allowed = false;
+        let iterable = self.parse_for_iterable(&in_token_span);
+        self.is_struct_initialization_allowed = true;
+        let iterable = iterable?;
+
+        let _ = self.expect_token(&Token::LeftBrace)?;
+
+        let body = self
+            .parse_statement_sequence()?
+            .map(|(statements, _)| statements);
+
+        let right_brace_token_span = self.expect_token(&Token::RightBrace)?;
+
+        Ok(UntypedStatement::For {
+            variable_name,
+            iterable,
+            body,
+            location: Location {
+                start: for_token_span.start,
+                end: right_brace_token_span.end,
+            },
+        })
+    }
+
+    fn parse_for_iterable(
+        &mut self,
+        in_token_span: &TokenSpan,
+    ) -> Result<UntypedIterable, ParsingError> {
+        let Some(first_expression) = self.parse_expression()? else {
+            return Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: in_token_span.token.clone(),
+                    expected: "range or array expression".to_string().into(),
+                },
+                location: LexLocation {
+                    start: in_token_span.start,
+                    end: in_token_span.end,
+                },
+            });
+        };
+
+        if let Some(range_token_span) = self.maybe_token(&Token::DotDot) {
+            let Some(end_expression) = self.parse_expression()? else {
+                return Err(ParsingError {
+                    error: MissingRightOperand,
+                    location: LexLocation {
+                        start: range_token_span.start,
+                        end: range_token_span.end,
+                    },
+                });
+            };
+
+            Ok(UntypedIterable::Range {
+                location: Location {
+                    start: first_expression.get_location().start,
+                    end: end_expression.get_location().end,
+                },
+                start: Box::new(first_expression),
+                end: Box::new(end_expression),
+            })
+        } else {
+            Ok(UntypedIterable::Array {
+                location: first_expression.get_location(),
+                array: Box::new(first_expression),
+            })
+        }
+    }
+
+    // NOTE: `else if` chains are desugared into an `else` body,
+    // that consists of a single nested `if` statement
+    fn parse_if_statement(
+        &mut self,
+        if_token_span: &TokenSpan,
+    ) -> Result<UntypedStatement, ParsingError> {
+        let _ = self.advance_token();
+        let _ = self.expect_token(&Token::LeftParenthesis)?;
+        let condition = self.parse_expression()?;
+        let _ = self.expect_token(&Token::RightParenthesis)?;
+
+        let _ = self.expect_token(&Token::LeftBrace)?;
+        let if_body = self
+            .parse_statement_sequence()?
+            .map(|(statements, _)| statements);
+
+        let right_brace_token_span = self.expect_token(&Token::RightBrace)?;
+        let mut end = right_brace_token_span.end;
+
+        let else_body = if let Some(Token::Else) = self.current_token.as_ref().map(|tok| &tok.token)
+        {
+            self.advance_token();
+
+            match self.current_token.clone() {
+                Some(token_span) if token_span.token == Token::If => {
+                    let else_if_statement = self.parse_if_statement(&token_span)?;
+                    end = else_if_statement.get_location().end;
+
+                    Some(vec1::vec1![else_if_statement])
+                }
+                _ => {
+                    let _ = self.expect_token(&Token::LeftBrace)?;
+
+                    let else_statements = self
+                        .parse_statement_sequence()?
+                        .map(|(statements, _)| statements);
+
+                    let else_right_brace = self.expect_token(&Token::RightBrace)?;
+                    end = else_right_brace.end;
+
+                    else_statements
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(UntypedStatement::If {
+            condition: Box::new(condition.unwrap()),
+            if_body,
+            else_body,
+            location: Location {
+                start: if_token_span.start,
+                end,
+            },
+        })
+    }
+
+    // NOTE: each arm is either an enum variant name with optional bindings
+    // for its payload (`circle(radius) { ... }`) or a wildcard (`_ { ... }`)
+    fn parse_match_statement(
+        &mut self,
+        match_token_span: &TokenSpan,
+    ) -> Result<UntypedStatement, ParsingError> {
+        let _ = self.advance_token();
+        let left_parenthesis_token_span = self.expect_token(&Token::LeftParenthesis)?;
+
+        let Some(value) = self.parse_expression()? else {
+            return Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: left_parenthesis_token_span.token,
+                    expected: "match value expression".to_string().into(),
+                },
+                location: LexLocation {
+                    start: left_parenthesis_token_span.start,
+                    end: left_parenthesis_token_span.end,
+                },
+            });
+        };
+
+        let _ = self.expect_token(&Token::RightParenthesis)?;
+        let _ = self.expect_token(&Token::LeftBrace)?;
+
+        let arms = self.parse_series(&Self::parse_match_arm, None)?;
+
+        let right_brace_token_span = self.expect_token(&Token::RightBrace)?;
+
+        Ok(UntypedStatement::Match {
+            value: Box::new(value),
+            arms: Vec1::try_from_vec(arms).ok(),
+            location: Location {
+                start: match_token_span.start,
+                end: right_brace_token_span.end,
+            },
+        })
+    }
+
+    fn parse_match_arm(&mut self) -> Result<Option<UntypedMatchArm>, ParsingError> {
+        let Some(
+            name_token_span @ TokenSpan {
+                token: Token::Name { .. },
+                ..
+            },
+        ) = self.current_token.clone()
+        else {
+            return Ok(None);
+        };
+
+        let _ = self.advance_token();
+
+        let Token::Name { value: name } = name_token_span.token else {
+            return Ok(None);
+        };
+
+        let pattern = if name == WILDCARD {
+            UntypedPattern::Wildcard {
+                location: Location {
+                    start: name_token_span.start,
+                    end: name_token_span.end,
+                },
+            }
+        } else if self.maybe_token(&Token::LeftParenthesis).is_some() {
+            let bindings = self.parse_series(&Self::parse_pattern_binding, Some(&Token::Comma))?;
+            let right_parenthesis_token_span = self.expect_token(&Token::RightParenthesis)?;
+
+            UntypedPattern::Variant {
+                location: Location {
+                    start: name_token_span.start,
+                    end: right_parenthesis_token_span.end,
+                },
+                variant_name: name,
+                bindings: Vec1::try_from_vec(bindings).ok(),
+            }
+        } else {
+            UntypedPattern::Variant {
+                location: Location {
+                    start: name_token_span.start,
+                    end: name_token_span.end,
+                },
+                variant_name: name,
+                bindings: None,
+            }
+        };
+
+        let _ = self.expect_token(&Token::LeftBrace)?;
+        let body = self
+            .parse_statement_sequence()?
+            .map(|(statements, _)| statements);
+        let right_brace_token_span = self.expect_token(&Token::RightBrace)?;
+
+        Ok(Some(UntypedMatchArm {
+            location: Location {
+                start: name_token_span.start,
+                end: right_brace_token_span.end,
+            },
+            pattern,
+            body,
+        }))
+    }
+
+    fn parse_pattern_binding(&mut self) -> Result<Option<EcoString>, ParsingError> {
+        let Some(TokenSpan {
+            token: Token::Name { value },
+            start,
+            end,
+        }) = self.current_token.clone()
+        else {
+            return Ok(None);
+        };
+
+        if let "true" | "false" | "nil" = value.as_str() {
+            return Err(ParsingError {
+                error: error::Type::InvalidName {
+                    token: Token::Name { value },
+                },
+                location: LexLocation { start, end },
+            });
+        }
+
+        let _ = self.advance_token();
+
+        Ok(Some(value))
+    }
+
+    // NOTE: `panic("message")` and `exit(code)` take exactly one parenthesized argument
+    fn parse_statement_argument(
+        &mut self,
+        keyword_token_span: &TokenSpan,
+    ) -> Result<(expression::UntypedExpression, u32), ParsingError> {
+        let _ = self.expect_token(&Token::LeftParenthesis)?;
+
+        let Some(argument) = self.parse_expression()? else {
+            return Err(ParsingError {
+                error: error::Type::UnexpectedToken {
+                    token: keyword_token_span.token.clone(),
+                    expected: format!("{} argument expression", keyword_token_span.token).into(),
+                },
+                location: LexLocation {
+                    start: keyword_token_span.start,
+                    end: keyword_token_span.end,
+                },
+            });
+        };
+
+        let right_parenthesis_token_span = self.expect_token(&Token::RightParenthesis)?;
+
+        Ok((argument, right_parenthesis_token_span.end))
+    }
+
     fn parse_assignment(
         &mut self,
         start: u32,
@@ -939,7 +1449,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
             });
         };
 
-        if let "true" | "false" = name.as_str() {
+        if let "true" | "false" | "nil" = name.as_str() {
             return Err(ParsingError {
                 error: error::Type::InvalidName {
                     token: name_token_span.token,
@@ -949,7 +1459,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                     end: name_token_span.end,
                 },
             });
-        };
+        }
 
         let Some(type_annotation) = self.parse_type_annotation()? else {
             return Err(ParsingError {
@@ -1121,6 +1631,20 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
     }
 
     fn parse_type_annotation(&mut self) -> Result<Option<UntypedType>, ParsingError> {
+        let Some(type_annotation) = self.parse_non_optional_type_annotation()? else {
+            return Ok(None);
+        };
+
+        if self.maybe_token(&Token::Question).is_some() {
+            return Ok(Some(UntypedType::Optional {
+                type_: Box::new(type_annotation),
+            }));
+        }
+
+        Ok(Some(type_annotation))
+    }
+
+    fn parse_non_optional_type_annotation(&mut self) -> Result<Option<UntypedType>, ParsingError> {
         match self.current_token.clone() {
             Some(token_span) => match token_span.token {
                 Token::Int => {
@@ -1139,6 +1663,10 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                     let _ = self.advance_token();
                     Ok(Some(UntypedType::Char))
                 }
+                Token::Bool => {
+                    let _ = self.advance_token();
+                    Ok(Some(UntypedType::Boolean))
+                }
                 Token::Name { value } => {
                     let name = value;
                     let _ = self.advance_token();
@@ -1217,9 +1745,7 @@ impl<T: Iterator<Item = LexResult>> Parser<T> {
                 Some(Ok(TokenSpan {
                     token: Token::Comment | Token::NewLine,
                     ..
-                })) => {
-                    continue;
-                }
+                })) => {}
                 Some(Ok(token)) => {
                     self.current_token = Some(token);
                     break;
@@ -1284,6 +1810,20 @@ fn token_to_binary_operator(token: &Token) -> Option<BinaryOperator> {
         Token::NotEqual => Some(BinaryOperator::NotEqual),
         Token::PipePipe => Some(BinaryOperator::Or),
         Token::AmpersandAmpersand => Some(BinaryOperator::And),
+        Token::Ampersand => Some(BinaryOperator::BitwiseAnd),
+        Token::Pipe => Some(BinaryOperator::BitwiseOr),
+        Token::Caret => Some(BinaryOperator::BitwiseXor),
+        Token::LessLess => Some(BinaryOperator::ShiftLeft),
+        Token::GreaterGreater => Some(BinaryOperator::ShiftRight),
+        _ => None,
+    }
+}
+
+fn token_to_unary_operator(token: &Token) -> Option<UnaryOperator> {
+    match token {
+        Token::Bang => Some(UnaryOperator::Not),
+        Token::Minus => Some(UnaryOperator::NegationInt),
+        Token::MinusFloat => Some(UnaryOperator::NegationFloat),
         _ => None,
     }
 }
@@ -63,6 +63,23 @@ impl ConvertingError {
             ConvertingErrorType::InvalidBooleanOperation => {
                 "logical operations require boolean expressions in both sides".to_owned()
             }
+            ConvertingErrorType::InvalidNotOperation => {
+                "logical negation requires a boolean expression".to_owned()
+            }
+            ConvertingErrorType::IntNegationInvalidType => {
+                "integer negation requires an integer expression".to_owned()
+            }
+            ConvertingErrorType::FloatNegationInvalidType => {
+                "float negation requires a float expression".to_owned()
+            }
+            ConvertingErrorType::NotIterable { found } => {
+                format!("type {found:?} is not iterable, expected int range or array")
+            }
+            ConvertingErrorType::PossiblyNilFieldAccess { variable_name } => {
+                format!("`{variable_name}` may be nil, compare it with `nil` before accessing its fields")
+            }
+            ConvertingErrorType::BreakOutsideLoop => "`break` outside of a loop".to_owned(),
+            ConvertingErrorType::ContinueOutsideLoop => "`continue` outside of a loop".to_owned(),
             ConvertingErrorType::UnsupportedBinaryOperation => {
                 "unsupported binary operation".to_owned()
             }
@@ -76,12 +93,51 @@ impl ConvertingError {
             ConvertingErrorType::VariableNotDefined { variable_name } => {
                 format!("variable `{variable_name:?}` is not defined")
             }
+            ConvertingErrorType::EnumNotDefined { enum_name } => {
+                format!("enum `{enum_name:?}` is not defined")
+            }
+            ConvertingErrorType::VariantNotFound {
+                enum_name,
+                variant_name,
+            } => {
+                format!("variant `{variant_name}` not found in enum `{enum_name}`")
+            }
+            ConvertingErrorType::VariantFieldsAmountMismatch {
+                variant_name,
+                expected,
+                found,
+            } => {
+                format!("variant `{variant_name}` has {expected} fields, found {found}")
+            }
+            ConvertingErrorType::NotMatchable { found } => {
+                format!("type {found:?} can not be matched, expected enum")
+            }
+            ConvertingErrorType::UnreachableMatchArm => {
+                "match arm is unreachable, as previous arms cover it".to_owned()
+            }
+            ConvertingErrorType::NonExhaustiveMatch {
+                enum_name,
+                missing_variants,
+            } => {
+                let missing_variants = missing_variants
+                    .iter()
+                    .map(|variant| format!("`{variant}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("match on enum `{enum_name}` is not exhaustive, missing variants: {missing_variants}")
+            }
             ConvertingErrorType::FieldNotFound {
                 field_name,
                 struct_name,
             } => {
                 format!("field `{field_name:?}` not found in struct `{struct_name:?}`")
             }
+            ConvertingErrorType::FieldNotInitialized {
+                struct_name,
+                field_name,
+            } => {
+                format!("field `{field_name}` of struct `{struct_name}` has no default value and must be initialized")
+            }
             ConvertingErrorType::TypeMismatch { expected, found } => {
                 format!("type mismatch: expected {expected:?}, found {found:?}")
             }
@@ -95,15 +151,15 @@ impl ConvertingError {
             ConvertingErrorType::ArrayMismatchType => {
                 "the second argument must be of the same type as the array".to_owned()
             }
-            ConvertingErrorType::InvalidHotswapMultipleDefinitions => {
-                "hotswap module must contain exactly one definition".to_owned()
+            ConvertingErrorType::MissingReturn { function_name } => {
+                format!("function `{function_name}` may reach its end without returning a value")
+            }
+            ConvertingErrorType::InvalidHotswapEmpty => {
+                "hotswap module must contain at least one function".to_owned()
             }
             ConvertingErrorType::InvalidHotswapNotFunction => {
                 "hotswap definition must be a function".to_owned()
             }
-            ConvertingErrorType::InvalidHotswapNameMismatch { expected, found } => {
-                format!("hotswap function name mismatch: expected `{expected}`, found `{found}`")
-            }
             ConvertingErrorType::InvalidHotswapReturnTypeMismatch { expected, found } => {
                 format!("hotswap return type mismatch: expected {expected:?}, found {found:?}")
             }
@@ -117,6 +173,43 @@ impl ConvertingError {
             } => {
                 format!("hotswap argument type mismatch at position {argument_index}: expected {expected:?}, found {found:?}")
             }
+            ConvertingErrorType::InvalidHotswapEnum { enum_name } => {
+                format!("hotswap cannot define enum `{enum_name}`, only functions and structs")
+            }
+            ConvertingErrorType::InvalidHotswapFieldTypeMismatch {
+                struct_name,
+                field_name,
+                expected,
+                found,
+            } => {
+                format!("hotswap type mismatch of field `{field_name}` in struct `{struct_name}`: expected {expected:?}, found {found:?}")
+            }
+            ConvertingErrorType::InvalidHotswapFieldWithoutDefault {
+                struct_name,
+                field_name,
+            } => {
+                format!("hotswap cannot add field `{field_name}` to struct `{struct_name}`, as it has no default value for live instances")
+            }
+            ConvertingErrorType::InvalidHotswapBreaksFunction {
+                function_name,
+                error,
+            } => {
+                let description = error.get_description();
+                format!("hotswap breaks function `{function_name}`: {description}")
+            }
+            ConvertingErrorType::InvalidHotswapCallersNotSwapped { callers, error } => {
+                let description = error.get_description();
+                let callers = callers
+                    .iter()
+                    .map(|caller| format!("`{caller}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{description}; callers {callers} must be hotswapped along with it")
+            }
+            ConvertingErrorType::InvalidHotswapActiveCaller { caller_name, error } => {
+                let description = error.get_description();
+                format!("{description}; caller `{caller_name}` is running and its call cannot be restarted")
+            }
             ConvertingErrorType::ParsingError { error } => {
                 let description = error.get_description();
                 format!("recieved parsing error: {description}")
@@ -137,6 +230,17 @@ pub enum ConvertingErrorType {
     StringOperationInvalidType,
     InvalidCharLiteral,
     InvalidBooleanOperation,
+    InvalidNotOperation,
+    IntNegationInvalidType,
+    FloatNegationInvalidType,
+    NotIterable {
+        found: crate::type_::Type,
+    },
+    PossiblyNilFieldAccess {
+        variable_name: EcoString,
+    },
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
     UnsupportedBinaryOperation,
     UnsupportedType,
     FunctionNotDefined {
@@ -148,10 +252,35 @@ pub enum ConvertingErrorType {
     VariableNotDefined {
         variable_name: EcoString,
     },
+    EnumNotDefined {
+        enum_name: EcoString,
+    },
+    VariantNotFound {
+        enum_name: EcoString,
+        variant_name: EcoString,
+    },
+    VariantFieldsAmountMismatch {
+        variant_name: EcoString,
+        expected: usize,
+        found: usize,
+    },
+    NotMatchable {
+        found: crate::type_::Type,
+    },
+    UnreachableMatchArm,
+    NonExhaustiveMatch {
+        enum_name: EcoString,
+        missing_variants: Vec<EcoString>,
+    },
     FieldNotFound {
         struct_name: EcoString,
         field_name: EcoString,
     },
+    /// Field holds an enum, directly or in a nested struct, which has no default value
+    FieldNotInitialized {
+        struct_name: EcoString,
+        field_name: EcoString,
+    },
     TypeMismatch {
         expected: crate::type_::Type,
         found: crate::type_::Type,
@@ -165,12 +294,12 @@ pub enum ConvertingErrorType {
         found: crate::type_::Type,
     },
     ArrayMismatchType,
-    InvalidHotswapMultipleDefinitions,
-    InvalidHotswapNotFunction,
-    InvalidHotswapNameMismatch {
-        expected: EcoString,
-        found: EcoString,
+    /// Function with a return type may reach its end without returning a value
+    MissingReturn {
+        function_name: EcoString,
     },
+    InvalidHotswapEmpty,
+    InvalidHotswapNotFunction,
     InvalidHotswapReturnTypeMismatch {
         expected: crate::type_::Type,
         found: crate::type_::Type,
@@ -184,4 +313,35 @@ pub enum ConvertingErrorType {
         expected: crate::type_::Type,
         found: crate::type_::Type,
     },
+    /// Enums cannot be hotswapped, as there is no migration for their values
+    InvalidHotswapEnum {
+        enum_name: EcoString,
+    },
+    /// Live instances of the struct cannot be migrated to the new field type
+    InvalidHotswapFieldTypeMismatch {
+        struct_name: EcoString,
+        field_name: EcoString,
+        expected: crate::type_::Type,
+        found: crate::type_::Type,
+    },
+    /// Added struct field holds an enum, so live instances have no value for it
+    InvalidHotswapFieldWithoutDefault {
+        struct_name: EcoString,
+        field_name: EcoString,
+    },
+    /// Function of the running program does not type check with hotswapped structs
+    InvalidHotswapBreaksFunction {
+        function_name: EcoString,
+        error: Box<ConvertingError>,
+    },
+    /// Function signature is changed, while some of its callers still pass old arguments
+    InvalidHotswapCallersNotSwapped {
+        callers: Vec<EcoString>,
+        error: Box<ConvertingError>,
+    },
+    /// Function signature is changed, while its caller runs in the outermost call
+    InvalidHotswapActiveCaller {
+        caller_name: EcoString,
+        error: Box<ConvertingError>,
+    },
 }
@@ -1,7 +1,12 @@
 use crate::{
+    ast::{
+        expression::UntypedExpression, location::Location as AstLocation,
+        statement::UntypedStatement,
+    },
     error::Error,
     lex::{error::LexicalError, location::Location},
     parse::error::ParsingError,
+    type_::UntypedType,
 };
 
 use super::{parse_module, parse_statement_sequence};
@@ -201,16 +206,8 @@ fn test_int_literals() {
 
 #[test]
 fn test_double_minus_sign() {
-    assert_error!(
-        "func main() { var a int = --1 }",
-        ParsingError {
-            error: crate::parse::error::Type::UnexpectedToken {
-                token: crate::lex::token::Token::Func,
-                expected: "function or struct definitions".into(),
-            },
-            location: Location { start: 0, end: 4 },
-        }
-    );
+    assert_parse_module!("func main() { var a int = --1 }");
+    assert_parse_module!("func main() { var a int = - -b }");
 }
 
 #[test]
@@ -228,7 +225,7 @@ fn test_float_literals() {
         }
     );
     assert_error!(
-        "123..",
+        "1.2..",
         ParsingError {
             error: crate::parse::error::Type::LexicalError {
                 error: LexicalError {
@@ -285,6 +282,27 @@ fn test_operators() {
     assert_parse_module!("func main() { 1 + 2 / 3 * 4 && 5 || 6 % 8.0 <> \"whatever\" }");
 }
 
+#[test]
+fn test_bitwise_operators() {
+    assert_parse_module!("func main() { var a int = b & 255 | c ^ d }");
+    assert_parse_module!("func main() { var a int = 1 << 4 >> b + 1 }");
+}
+
+#[test]
+fn test_unary_operators() {
+    assert_parse_module!("func main() { var a int = -b * 2 }");
+    assert_parse_module!("func main() { var a float = -.b +. 1.5 }");
+    assert_parse_module!("func main() { var a bool = !(b == 0) && !!c }");
+    assert_parse_module!("func main() { var a int = 0 - -fn(b) }");
+    assert_error!(
+        "!",
+        ParsingError {
+            error: crate::parse::error::Type::MissingRightOperand,
+            location: Location { start: 0, end: 0 },
+        }
+    );
+}
+
 #[test]
 fn test_struct_definition() {
     assert_parse_module!("struct idk {}");
@@ -296,6 +314,162 @@ fn test_function_with_if_statement() {
     assert_parse_module!("func factorial(n int) int {\nif (2 > 0) {\nreturn 2\n}\nreturn 1\n}");
 }
 
+#[test]
+fn test_else_if_chain() {
+    assert_parse_module!(
+        "func main() {\nif (a) {\n} else if (b) {\nc = 1\n} else if (d) {\n} else {\nc = 2\n}\n}"
+    );
+
+    let statements =
+        parse_statement_sequence("if (a) { c = 0 } else if (b) { c = 1 } else { c = 2 }")
+            .expect("should parse");
+
+    let UntypedStatement::If {
+        else_body: Some(else_body),
+        location,
+        ..
+    } = statements.first()
+    else {
+        panic!("expected if statement");
+    };
+
+    assert_eq!(location.end, 52);
+    assert_eq!(else_body.len(), 1);
+    assert!(matches!(
+        else_body.first(),
+        UntypedStatement::If {
+            else_body: Some(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_boolean_literals() {
+    assert_parse_module!("struct flags { is_set bool }\nfunc main(a bool) bool { var b []bool = []bool{true, false}\nreturn !a && b[0] }");
+
+    let statements = parse_statement_sequence("var flag bool = false").expect("should parse");
+
+    let UntypedStatement::Assignment(assignment) = statements.first() else {
+        panic!("expected assignment");
+    };
+
+    assert_eq!(assignment.type_annotation, UntypedType::Boolean);
+    assert_eq!(
+        *assignment.value,
+        UntypedExpression::BooleanLiteral {
+            location: AstLocation { start: 16, end: 20 },
+            value: false,
+        }
+    );
+}
+
+#[test]
+fn test_optional_types() {
+    assert_parse_module!("struct node { next node? }\nfunc main(a node?) node? { var b []node? = []node?{nil, a}\nif (a != nil) { return a }\nreturn nil }");
+
+    let statements = parse_statement_sequence("var head node? = nil").expect("should parse");
+
+    let UntypedStatement::Assignment(assignment) = statements.first() else {
+        panic!("expected assignment");
+    };
+
+    assert_eq!(
+        assignment.type_annotation,
+        UntypedType::Optional {
+            type_: Box::new(UntypedType::Custom {
+                name: "node".into()
+            })
+        }
+    );
+    assert_eq!(
+        *assignment.value,
+        UntypedExpression::NilLiteral {
+            location: AstLocation { start: 17, end: 19 },
+        }
+    );
+}
+
+#[test]
+fn test_enums_and_match() {
+    assert_parse_module!("enum shape { circle(radius float) rectangle(width float, height float) point }\nfunc main() { var s shape = shape::circle(1.0)\nmatch (s) { circle(r) { println(r) } rectangle(_, h) {} _ {} } }");
+    assert_parse_module!("enum empty {}\nfunc main() { match (e) {} }");
+
+    let statements = parse_statement_sequence("var s shape = shape::point").expect("should parse");
+
+    let UntypedStatement::Assignment(assignment) = statements.first() else {
+        panic!("expected assignment");
+    };
+
+    assert_eq!(
+        *assignment.value,
+        UntypedExpression::EnumInitialization {
+            location: AstLocation { start: 14, end: 25 },
+            enum_name: "shape".into(),
+            variant_name: "point".into(),
+            arguments: None,
+        }
+    );
+
+    assert_error!(
+        "match (s) { circle(nil) {} }",
+        ParsingError {
+            error: crate::parse::error::Type::InvalidName {
+                token: crate::lex::token::Token::Name {
+                    value: "nil".into()
+                },
+            },
+            location: Location { start: 19, end: 22 },
+        }
+    );
+}
+
+#[test]
+fn test_panic_and_exit() {
+    assert_parse_module!("func main() { if (a) { panic(\"unreachable\") }\nexit(a + 1) }");
+    assert_error!(
+        "panic()",
+        ParsingError {
+            error: crate::parse::error::Type::UnexpectedToken {
+                token: crate::lex::token::Token::Panic,
+                expected: "`panic` argument expression".into(),
+            },
+            location: Location { start: 0, end: 5 },
+        }
+    );
+    assert_error!(
+        "exit",
+        ParsingError {
+            error: crate::parse::error::Type::UnexpectedEof,
+            location: Location { start: 0, end: 0 },
+        }
+    );
+}
+
+#[test]
+fn test_loop_control_statements() {
+    assert_parse_module!("func main() { loop { if (a) { continue } else { break } } }");
+    assert_parse!("loop {\ncontinue\nbreak\n}");
+}
+
+#[test]
+fn test_loop_forms() {
+    assert_parse_module!("func main() { loop (i < n) { i = i + 1 } }");
+    assert_parse_module!("func main() { for i in 0..n { println(i) } }");
+    assert_parse_module!("func main() { for i in a + 1..len(b) - 1 {} }");
+    assert_parse_module!("func main() { for element in elements { println(element) } }");
+    assert_error!(
+        "for 1 in 0..2 {}",
+        ParsingError {
+            error: crate::parse::error::Type::UnexpectedToken {
+                token: crate::lex::token::Token::IntLiteral { value: "1".into() },
+                expected: "loop variable name".into(),
+            },
+            location: Location { start: 4, end: 5 },
+        }
+    );
+}
+
 #[test]
 fn test_array_element_access_expression() {
     assert_parse_module!("func main() {arr[i + 2] = 2}");
@@ -8,10 +8,17 @@ pub enum Type {
     Custom {
         name: EcoString,
     },
+    Enum {
+        name: EcoString,
+    },
     Array {
         type_: Box<Self>, // Needed for empty array
     },
     Boolean,
+    Optional {
+        type_: Box<Self>,
+    },
+    Nil,
     Void,
 }
 
@@ -28,4 +35,7 @@ pub enum UntypedType {
         type_: Box<Self>, // Needed for empty array
     },
     Boolean,
+    Optional {
+        type_: Box<Self>,
+    },
 }
@@ -1,19 +1,26 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Write};
 
 use ecow::EcoString;
+pub mod error;
 pub mod instruction;
 
 mod gc;
 #[cfg(test)]
 pub mod tests;
 
-use gc::{Object, GC};
-use instruction::{Bytecode, Instruction, Value};
+use error::RuntimeError;
+use gc::{HeapLimitReached, Object, GC};
+use instruction::{Bytecode, Handle, Instruction, Locations, Value};
 
-use crate::optimizer::Optimizer;
+use crate::{ast::location::Location, optimizer::Optimizer};
+
+/// Maximum amount of nested calls, unless set with [`VM::set_max_call_depth`]
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1_000_000;
 
 pub struct VM {
     pub(crate) input: Bytecode,
+    /// Location of the awa code, each instruction of `input` was produced from
+    pub(crate) locations: Vec<Option<CodeLocation>>,
     pub(crate) program_counter: usize,
     pub(crate) stack: Vec<Value>,
 
@@ -26,17 +33,57 @@ pub struct VM {
 
     pub(crate) gc: GC,
 
-    backup_state: Option<State>,
+    /// Saved state of each active call, to roll back to in case of backoff
+    pub(crate) backup_states: Vec<State>,
     execution_stats: ExecutionStats,
     optimization_threshold: u32,
 
     should_perform_optimizations: bool,
+    /// Whether backup states keep copies of heap objects, mutated by their calls
+    should_snapshot_heap: bool,
+    /// Maximum amount of nested calls, `main()` excluded
+    max_call_depth: usize,
+    /// Amount of instructions, left to run, unlimited if `None`
+    fuel: Option<u64>,
+    /// Amount of sources, that code of the VM was produced from
+    sources_count: usize,
+}
+
+/// Location in the awa code, that instruction was produced from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeLocation {
+    /// `0` for the code VM was created with, then each hotswap adds the next one
+    pub source: usize,
+    pub location: Location,
+}
+
+/// Frame of the awa call stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    pub function_name: EcoString,
+    /// Location of the code, executed in the frame: the failed instruction
+    /// for the innermost frame and the call of the next frame for the others
+    pub location: Option<CodeLocation>,
+    /// Local variables of the frame and their printed values, sorted by name
+    pub locals: Vec<(EcoString, String)>,
 }
 
+/// Saved state of the caller at the call of the function
 #[derive(Debug)]
-struct State {
-    stack: Vec<Value>,
-    program_counter: usize,
+pub(crate) struct State {
+    /// Address of the `Call` instruction
+    pub(crate) program_counter: usize,
+    /// Height of the stack at the call, arguments of the call included
+    pub(crate) stack_height: usize,
+    /// Values below `stack_height`, popped by the function, in order of popping
+    pub(crate) popped_values: Vec<Value>,
+    /// Height of the environments stack at the call
+    pub(crate) environments_height: usize,
+    /// Height of the heap at the call, objects above it were allocated by the function
+    pub(crate) heap_height: usize,
+    /// Objects allocated before the call, as they were before the function
+    /// first mutated them, indexed by handle
+    pub(crate) heap_snapshots: HashMap<usize, Object>,
 }
 
 #[derive(Default)]
@@ -100,7 +147,16 @@ impl ExecutionStats {
 
 #[derive(Debug, Clone)]
 pub enum RunCommunication {
-    RequireHotswap(EcoString),
+    /// Awa code failed, VM stays at the failed instruction. Execution can be
+    /// rolled back with `VM::perform_backoff` if the error is recoverable
+    RuntimeError(RuntimeError),
+    Panicked {
+        message: EcoString,
+        location: Option<CodeLocation>,
+        backtrace: Vec<StackFrame>,
+    },
+    /// Awa code called `exit` with the status code
+    Exited(u8),
     Finished,
 }
 
@@ -112,8 +168,35 @@ impl VM {
     /// Will panic if the provided bytecode does not contain `main()` function.
     #[must_use]
     pub fn new(input: Vec<Instruction>, should_perform_optimizations: bool) -> Self {
+        Self::new_with_locations(input, Vec::new(), should_perform_optimizations)
+    }
+
+    /// Initializes new VM, that reports runtime errors at the provided
+    /// locations of the instructions
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the provided bytecode does not contain `main()` function.
+    #[must_use]
+    pub fn new_with_locations(
+        input: Vec<Instruction>,
+        locations: Locations,
+        should_perform_optimizations: bool,
+    ) -> Self {
+        let mut locations = locations
+            .into_iter()
+            .map(|location| {
+                location.map(|location| CodeLocation {
+                    source: 0,
+                    location,
+                })
+            })
+            .collect::<Vec<_>>();
+        locations.resize(input.len(), None);
+
         let mut vm = Self {
             input,
+            locations,
             program_counter: 0,
             stack: Vec::with_capacity(100_000),
             environments_stack: Vec::with_capacity(100_000),
@@ -121,10 +204,14 @@ impl VM {
             functions: HashMap::with_capacity(10),
             call_stack: Vec::with_capacity(100_000),
             gc: GC::new(),
-            backup_state: None,
+            backup_states: Vec::with_capacity(100_000),
             execution_stats: ExecutionStats::new(0),
             optimization_threshold: 10000,
             should_perform_optimizations,
+            should_snapshot_heap: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            fuel: None,
+            sources_count: 1,
         };
 
         vm.environments_stack.push(HashMap::with_capacity(50));
@@ -134,11 +221,6 @@ impl VM {
 
         if let Some(&main_address) = vm.functions.get("main") {
             vm.program_counter = main_address;
-
-            vm.backup_state = Some(State {
-                stack: vm.stack.clone(),
-                program_counter: vm.program_counter,
-            });
         } else {
             panic!("cannot find function `main()` in provided code");
         }
@@ -146,20 +228,65 @@ impl VM {
         vm
     }
 
+    /// Makes each call keep a copy of every heap object it mutates, so that
+    /// backoff rolls the heap back along with the stack
+    ///
+    /// Copies cost time and memory of the whole mutated object, so it is off by default
+    pub fn set_heap_snapshots(&mut self, enabled: bool) {
+        self.should_snapshot_heap = enabled;
+    }
+
+    /// Limits amount of nested calls, `main()` excluded. Deeper call fails with
+    /// [`RuntimeError::StackOverflow`]
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Limits amount of instructions, VM runs from now on. Running out of them
+    /// fails with [`RuntimeError::OutOfFuel`], then execution can be continued
+    /// by giving more fuel
+    pub fn set_fuel(&mut self, fuel: u64) {
+        self.fuel = Some(fuel);
+    }
+
+    /// Limits amount of objects on the heap. Allocation of an object, that
+    /// does not fit even after garbage collection, fails with
+    /// [`RuntimeError::HeapLimitExceeded`]
+    pub fn set_max_heap_size(&mut self, objects: usize) {
+        self.gc.max_heap_size = Some(objects);
+    }
+
     /// Runs one current instruction in the VM
     ///
     /// # Panics
     ///
-    /// Will panic if cannot recover from user code error, or in case other
-    /// interpreter parts do not function as expected.
+    /// Will panic in case other interpreter parts do not function as expected.
     #[must_use]
     pub fn run(&mut self) -> Option<RunCommunication> {
         if self.program_counter >= self.input.len() {
             return Some(RunCommunication::Finished);
         }
 
+        if let Some(fuel) = &mut self.fuel {
+            if *fuel == 0 {
+                return Some(RunCommunication::RuntimeError(RuntimeError::OutOfFuel {
+                    program_counter: self.program_counter,
+                }));
+            }
+
+            *fuel -= 1;
+        }
+
         let instruction = self.input[self.program_counter].clone();
 
+        self.execute(instruction)
+            .unwrap_or_else(|error| Some(RunCommunication::RuntimeError(error)))
+    }
+
+    fn execute(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<Option<RunCommunication>, RuntimeError> {
         match instruction {
             Instruction::PushInt(int) => {
                 self.stack.push(Value::Int(int));
@@ -170,20 +297,26 @@ impl VM {
             Instruction::PushChar(char) => {
                 self.stack.push(Value::Char(char));
             }
+            Instruction::PushBool(bool) => {
+                self.stack.push(Value::Bool(bool));
+            }
+            Instruction::PushNil => {
+                self.stack.push(Value::Nil);
+            }
             Instruction::PushString(string) => {
-                let handle = self.gc.allocate(Object::String(string));
+                let handle = self.allocate(Object::String(string))?;
 
                 self.stack.push(Value::Ref(handle));
                 self.maybe_run_gc();
             }
             Instruction::PushArray(array) => {
-                let handle = self.gc.allocate(Object::Array(array));
+                let handle = self.allocate(Object::Array(array))?;
 
                 self.stack.push(Value::Ref(handle));
                 self.maybe_run_gc();
             }
             Instruction::StoreInMap(name) => {
-                let value = self.stack.pop().expect("stack underflow");
+                let value = self.pop()?;
 
                 if let Some(environment) = self.environments_stack.last_mut() {
                     environment.insert(name, value);
@@ -192,251 +325,391 @@ impl VM {
                 }
             }
             Instruction::LoadToStack(name) => {
-                let variable_value = self.lookup_variable(&name);
+                let variable_value = self.lookup_variable(&name)?;
                 self.stack.push(variable_value.clone());
             }
             Instruction::AddInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
-                self.stack.push(Value::Int(lhs + rhs));
+                let result = lhs
+                    .checked_add(rhs)
+                    .ok_or_else(|| self.integer_overflow(format!("{lhs} + {rhs}")))?;
+
+                self.stack.push(Value::Int(result));
             }
             Instruction::SubInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
+
+                let result = lhs
+                    .checked_sub(rhs)
+                    .ok_or_else(|| self.integer_overflow(format!("{lhs} - {rhs}")))?;
 
-                self.stack.push(Value::Int(lhs - rhs));
+                self.stack.push(Value::Int(result));
             }
-            Instruction::MulInt | Instruction::And => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+            Instruction::MulInt => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
-                self.stack.push(Value::Int(lhs * rhs));
+                let result = lhs
+                    .checked_mul(rhs)
+                    .ok_or_else(|| self.integer_overflow(format!("{lhs} * {rhs}")))?;
+
+                self.stack.push(Value::Int(result));
             }
             Instruction::DivInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
                 if rhs == 0 {
-                    return Some(RunCommunication::RequireHotswap(
-                        self.perform_backoff("integer division by zero"),
-                    ));
+                    return Err(RuntimeError::DivisionByZero {
+                        program_counter: self.program_counter,
+                        dividend: Value::Int(lhs),
+                    });
                 }
 
-                self.stack.push(Value::Int(lhs / rhs));
+                let result = lhs
+                    .checked_div(rhs)
+                    .ok_or_else(|| self.integer_overflow(format!("{lhs} / {rhs}")))?;
+
+                self.stack.push(Value::Int(result));
             }
             Instruction::Mod => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
                 if rhs == 0 {
-                    return Some(RunCommunication::RequireHotswap(
-                        self.perform_backoff("modulo by zero"),
-                    ));
+                    return Err(RuntimeError::ModuloByZero {
+                        program_counter: self.program_counter,
+                        dividend: lhs,
+                    });
                 }
 
-                self.stack.push(Value::Int(lhs % rhs));
+                let result = lhs
+                    .checked_rem(rhs)
+                    .ok_or_else(|| self.integer_overflow(format!("{lhs} % {rhs}")))?;
+
+                self.stack.push(Value::Int(result));
+            }
+            Instruction::BitwiseAnd => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
+
+                self.stack.push(Value::Int(lhs & rhs));
+            }
+            Instruction::BitwiseXor => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
+
+                self.stack.push(Value::Int(lhs ^ rhs));
+            }
+            Instruction::ShiftLeft | Instruction::ShiftRight => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
+
+                let result = u32::try_from(rhs).ok().and_then(|rhs| match instruction {
+                    Instruction::ShiftLeft => lhs.checked_shl(rhs),
+                    _ => lhs.checked_shr(rhs),
+                });
+
+                let Some(result) = result else {
+                    return Err(RuntimeError::ShiftOutOfRange {
+                        program_counter: self.program_counter,
+                        amount: rhs,
+                    });
+                };
+
+                self.stack.push(Value::Int(result));
+            }
+            Instruction::NegateInt => {
+                let operand = self.pop()?;
+                let operand = self.get_int(&operand)?;
+
+                let result = operand
+                    .checked_neg()
+                    .ok_or_else(|| self.integer_overflow(format!("-({operand})")))?;
+
+                self.stack.push(Value::Int(result));
+            }
+            Instruction::WrappingAddInt
+            | Instruction::WrappingSubInt
+            | Instruction::WrappingMulInt
+            | Instruction::SaturatingAddInt
+            | Instruction::SaturatingSubInt
+            | Instruction::SaturatingMulInt => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
+
+                let result = match instruction {
+                    Instruction::WrappingAddInt => lhs.wrapping_add(rhs),
+                    Instruction::WrappingSubInt => lhs.wrapping_sub(rhs),
+                    Instruction::WrappingMulInt => lhs.wrapping_mul(rhs),
+                    Instruction::SaturatingAddInt => lhs.saturating_add(rhs),
+                    Instruction::SaturatingSubInt => lhs.saturating_sub(rhs),
+                    _ => lhs.saturating_mul(rhs),
+                };
+
+                self.stack.push(Value::Int(result));
             }
             Instruction::AddFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
                 self.stack.push(Value::Float(lhs + rhs));
             }
             Instruction::SubFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
                 self.stack.push(Value::Float(lhs - rhs));
             }
             Instruction::MulFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
                 self.stack.push(Value::Float(lhs * rhs));
             }
             Instruction::DivFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
                 if rhs == 0.0 {
-                    return Some(RunCommunication::RequireHotswap(
-                        self.perform_backoff("floating point division by zero"),
-                    ));
+                    return Err(RuntimeError::DivisionByZero {
+                        program_counter: self.program_counter,
+                        dividend: Value::Float(lhs),
+                    });
                 }
 
                 self.stack.push(Value::Float(lhs / rhs));
             }
+            Instruction::NegateFloat => {
+                let operand = self.pop()?;
+                let operand = self.get_float(&operand)?;
+
+                self.stack.push(Value::Float(-operand));
+            }
             Instruction::Append => {
-                let value = self.stack.pop().expect("stack underflow");
-                let array = self.stack.pop().expect("stack underflow");
+                let value = self.pop()?;
+                let array = self.pop()?;
 
                 if let Value::Ref(handle) = array {
+                    self.snapshot_object(handle);
+
                     if let Object::Array(ref mut slice) = self.gc.get_mut(handle) {
                         slice.push(value);
                     } else {
-                        panic!("Append to non-slice");
+                        return Err(self.type_mismatch("array", Value::Ref(handle)));
                     }
                     self.stack.push(Value::Ref(handle));
                 } else {
-                    panic!("Append expects Ref");
+                    return Err(self.type_mismatch("array", array));
                 }
             }
             Instruction::Pop => {
-                let array = self.stack.pop().expect("stack underflow");
+                let array = self.pop()?;
 
                 if let Value::Ref(handle) = array {
+                    self.snapshot_object(handle);
+
                     if let Object::Array(ref mut slice) = self.gc.get_mut(handle) {
-                        slice.pop();
+                        if slice.pop().is_none() {
+                            return Err(RuntimeError::PopFromEmptyArray {
+                                program_counter: self.program_counter,
+                            });
+                        }
                     } else {
-                        panic!("Append to non-slice");
+                        return Err(self.type_mismatch("array", Value::Ref(handle)));
                     }
                     self.stack.push(Value::Ref(handle));
                 } else {
-                    panic!("Append expects Ref");
+                    return Err(self.type_mismatch("array", array));
                 }
             }
             Instruction::GetByIndex => {
-                let index = self.stack.pop().expect("stack underflow");
-                let index = VM::get_int(&index);
-                let array = self.stack.pop().expect("stack underflow");
+                let index = self.pop()?;
+                let index = self.get_int(&index)?;
+                let array = self.pop()?;
 
                 if let Value::Ref(handle) = array {
                     let Object::Array(slice) = self.gc.get(handle) else {
-                        panic!("GetByIndex on non-slice");
+                        return Err(self.type_mismatch("array", Value::Ref(handle)));
                     };
 
                     if index < 0 || (usize::try_from(index).unwrap()) >= slice.len() {
-                        return Some(RunCommunication::RequireHotswap(
-                            self.perform_backoff("getting from array by index out of range"),
-                        ));
+                        return Err(RuntimeError::IndexOutOfRange {
+                            program_counter: self.program_counter,
+                            index,
+                            length: slice.len(),
+                        });
                     }
 
                     self.stack
                         .push(slice[usize::try_from(index).unwrap()].clone());
                 } else {
-                    panic!("GetByIndex expects Ref");
+                    return Err(self.type_mismatch("array", array));
                 }
             }
             Instruction::SetByIndex => {
-                let index = self.stack.pop().expect("stack underflow");
-                let index = VM::get_int(&index);
-                let value = self.stack.pop().expect("stack underflow");
-                let array = self.stack.pop().expect("stack underflow");
+                let index = self.pop()?;
+                let index = self.get_int(&index)?;
+                let value = self.pop()?;
+                let array = self.pop()?;
 
                 if let Value::Ref(handle) = array {
+                    self.snapshot_object(handle);
+
                     let Object::Array(slice) = self.gc.get_mut(handle) else {
-                        panic!("SetByIndex on non-slice");
+                        return Err(self.type_mismatch("array", Value::Ref(handle)));
                     };
 
                     if index < 0 || (usize::try_from(index).unwrap()) >= slice.len() {
-                        return Some(RunCommunication::RequireHotswap(
-                            self.perform_backoff("setting array value by index out of range"),
-                        ));
+                        return Err(RuntimeError::IndexOutOfRange {
+                            program_counter: self.program_counter,
+                            index,
+                            length: slice.len(),
+                        });
                     }
 
                     slice[usize::try_from(index).unwrap()] = value;
 
                     self.stack.push(Value::Ref(handle));
                 } else {
-                    panic!("SetByIndex expects Ref");
+                    return Err(self.type_mismatch("array", array));
+                }
+            }
+            Instruction::Length => {
+                let array = self.pop()?;
+
+                if let Value::Ref(handle) = array {
+                    let Object::Array(slice) = self.gc.get(handle) else {
+                        return Err(self.type_mismatch("array", Value::Ref(handle)));
+                    };
+
+                    self.stack
+                        .push(Value::Int(i64::try_from(slice.len()).unwrap()));
+                } else {
+                    return Err(self.type_mismatch("array", array));
                 }
             }
             Instruction::Equal => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let equal = self.is_equal_values(lhs, rhs);
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let equal = self.is_equal_values(lhs, rhs)?;
 
-                self.stack.push(Value::Int(i64::from(equal)));
+                self.stack.push(Value::Bool(equal));
             }
             Instruction::NotEqual => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let equal = self.is_equal_values(lhs, rhs);
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let equal = self.is_equal_values(lhs, rhs)?;
 
-                self.stack.push(Value::Int(i64::from(!equal)));
+                self.stack.push(Value::Bool(!equal));
             }
-            Instruction::Or => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+            Instruction::BitwiseOr => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
                 self.stack.push(Value::Int(lhs | rhs));
             }
+            Instruction::And => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_bool(&lhs)?, self.get_bool(&rhs)?);
+
+                self.stack.push(Value::Bool(lhs && rhs));
+            }
+            Instruction::Or => {
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_bool(&lhs)?, self.get_bool(&rhs)?);
+
+                self.stack.push(Value::Bool(lhs || rhs));
+            }
+            Instruction::Not => {
+                let operand = self.pop()?;
+
+                self.stack.push(Value::Bool(!VM::is_true(operand)));
+            }
             Instruction::LessInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs < rhs)));
+                self.stack.push(Value::Bool(lhs < rhs));
             }
             Instruction::LessEqualInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs <= rhs)));
+                self.stack.push(Value::Bool(lhs <= rhs));
             }
             Instruction::GreaterInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs > rhs)));
+                self.stack.push(Value::Bool(lhs > rhs));
             }
             Instruction::GreaterEqualInt => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_int(&lhs), VM::get_int(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_int(&lhs)?, self.get_int(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs >= rhs)));
+                self.stack.push(Value::Bool(lhs >= rhs));
             }
             Instruction::LessFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs < rhs)));
+                self.stack.push(Value::Bool(lhs < rhs));
             }
             Instruction::LessEqualFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs <= rhs)));
+                self.stack.push(Value::Bool(lhs <= rhs));
             }
             Instruction::GreaterFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs > rhs)));
+                self.stack.push(Value::Bool(lhs > rhs));
             }
             Instruction::GreaterEqualFloat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
-                let (lhs, rhs) = (VM::get_float(&lhs), VM::get_float(&rhs));
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
+                let (lhs, rhs) = (self.get_float(&lhs)?, self.get_float(&rhs)?);
 
-                self.stack.push(Value::Int(i64::from(lhs >= rhs)));
+                self.stack.push(Value::Bool(lhs >= rhs));
             }
             Instruction::Concat => {
-                let rhs = self.stack.pop().expect("stack underflow");
-                let lhs = self.stack.pop().expect("stack underflow");
+                let rhs = self.pop()?;
+                let lhs = self.pop()?;
 
-                let s1 = self.get_string(lhs);
-                let s2 = self.get_string(rhs);
+                let s1 = self.get_string(lhs)?;
+                let s2 = self.get_string(rhs)?;
 
                 let result = s1 + s2;
-                let handle = self.gc.allocate(Object::String(result));
+                let handle = self.allocate(Object::String(result))?;
 
                 self.stack.push(Value::Ref(handle));
                 self.maybe_run_gc();
@@ -457,25 +730,25 @@ impl VM {
                 assert!(address < self.input.len(), "jump out of range");
                 self.program_counter = address;
 
-                return None;
+                return Ok(None);
             }
             Instruction::JumpIfTrue(address) => {
-                let condition = self.stack.pop().expect("stack underflow");
+                let condition = self.pop()?;
 
                 if VM::is_true(condition) {
                     assert!(address < self.input.len(), "jump out of range");
                     self.program_counter = address;
 
-                    return None;
+                    return Ok(None);
                 }
             }
             Instruction::JumpIfFalse(address) => {
-                let condition = self.stack.pop().expect("stack underflow");
+                let condition = self.pop()?;
                 if !VM::is_true(condition) {
                     assert!(address < self.input.len(), "jump out of range");
                     self.program_counter = address;
 
-                    return None;
+                    return Ok(None);
                 }
             }
             Instruction::Call(name) => {
@@ -492,86 +765,113 @@ impl VM {
                 }
 
                 if let Some(&address) = self.functions.get(&name) {
-                    self.backup_state = Some(State {
-                        stack: self.stack.clone(),
+                    if self.call_stack.len() >= self.max_call_depth {
+                        return Err(RuntimeError::StackOverflow {
+                            program_counter: self.program_counter,
+                            depth: self.call_stack.len(),
+                        });
+                    }
+
+                    self.backup_states.push(State {
                         program_counter: self.program_counter,
+                        stack_height: self.stack.len(),
+                        popped_values: Vec::new(),
+                        environments_height: self.environments_stack.len(),
+                        heap_height: self.gc.heap.len(),
+                        heap_snapshots: HashMap::new(),
                     });
 
                     self.environments_stack.push(HashMap::with_capacity(10));
                     self.call_stack.push(self.program_counter + 1);
                     self.program_counter = address;
 
-                    return None;
+                    return Ok(None);
                 }
 
-                panic!("Call to undefined function `{name}`");
+                return Err(RuntimeError::UndefinedFunction {
+                    program_counter: self.program_counter,
+                    function_name: name,
+                });
             }
             Instruction::Return => {
                 self.environments_stack.pop();
 
                 if let Some(address) = self.call_stack.pop() {
                     self.program_counter = address;
-                    self.backup_state = None;
+                    self.discard_backup_state();
 
-                    return None;
+                    return Ok(None);
                 }
-                return None;
+                return Ok(None);
             }
-            Instruction::Struct(_) | Instruction::EndStruct => {
-                panic!("Struct definition in `main()` body");
+            Instruction::Struct(_)
+            | Instruction::EndStruct
+            | Instruction::Field(_, _)
+            | Instruction::Func(_)
+            | Instruction::EndFunc => {
+                return Err(RuntimeError::UnexpectedInstruction {
+                    program_counter: self.program_counter,
+                    instruction,
+                });
             }
             Instruction::NewStruct(struct_name) => {
-                if let Some(fields) = self.structures.get(&struct_name) {
-                    let mut map = HashMap::with_capacity(fields.len());
-
-                    for (key, value) in fields {
-                        map.insert(key.clone(), value.clone());
-                    }
-
-                    let handle = self.gc.allocate(Object::Struct {
-                        name: struct_name.clone(),
-                        fields: map,
+                if !self.structures.contains_key(&struct_name) {
+                    return Err(RuntimeError::UnknownStruct {
+                        program_counter: self.program_counter,
+                        struct_name,
                     });
-
-                    self.stack.push(Value::Ref(handle));
-                    self.maybe_run_gc();
-                } else {
-                    panic!("unknown struct `{struct_name}`");
                 }
-            }
-            Instruction::Field(_, _) => {
-                panic!("Field encountered in `main()` body");
+
+                let value = self.instantiate_struct(&struct_name, &mut Vec::new())?;
+
+                self.stack.push(value);
+                self.maybe_run_gc();
             }
             Instruction::SetField(field_name) => {
-                let value = self.stack.pop().expect("stack underflow");
-                let struct_value = self.stack.pop().expect("stack underflow");
+                let value = self.pop()?;
+                let struct_value = self.pop()?;
                 if let Value::Ref(handle) = struct_value {
-                    if let Object::Struct { fields, .. } = self.gc.get_mut(handle) {
+                    self.snapshot_object(handle);
+
+                    if let Object::Struct { name, fields } = self.gc.get_mut(handle) {
                         if fields.contains_key(&field_name) {
                             fields.insert(field_name.clone(), value);
                         } else {
-                            panic!("no such field: `{field_name}`");
+                            return Err(RuntimeError::MissingField {
+                                program_counter: self.program_counter,
+                                struct_name: name.clone(),
+                                field_name,
+                            });
                         }
                     } else {
-                        panic!("SetField on non-struct");
+                        return Err(self.type_mismatch("struct", Value::Ref(handle)));
                     }
                     self.stack.push(Value::Ref(handle));
+                } else if let Value::Nil = struct_value {
+                    return Err(RuntimeError::NilFieldAccess {
+                        program_counter: self.program_counter,
+                        field_name,
+                    });
                 } else {
-                    panic!("SetField expects struct ref");
+                    return Err(self.type_mismatch("struct", struct_value));
                 }
             }
             Instruction::GetField(field_name) => {
-                let struct_value = self.stack.pop().expect("stack underflow");
+                let struct_value = self.pop()?;
                 match struct_value {
                     Value::Ref(handle) => {
-                        if let Object::Struct { fields, .. } = self.gc.get(handle) {
+                        if let Object::Struct { name, fields } = self.gc.get(handle) {
                             if let Some(value) = fields.get(&field_name) {
                                 self.stack.push(value.clone());
                             } else {
-                                panic!("no such field: `{field_name}`");
+                                return Err(RuntimeError::MissingField {
+                                    program_counter: self.program_counter,
+                                    struct_name: name.clone(),
+                                    field_name,
+                                });
                             }
                         } else {
-                            panic!("GetField on non-struct Ref");
+                            return Err(self.type_mismatch("struct", Value::Ref(handle)));
                         }
                     }
                     Value::Struct { name, .. } => {
@@ -579,43 +879,137 @@ impl VM {
                             if let Some(value) = fields.get(&field_name) {
                                 self.stack.push(value.clone());
                             } else {
-                                panic!("no such field: `{field_name}`");
+                                return Err(RuntimeError::MissingField {
+                                    program_counter: self.program_counter,
+                                    struct_name: name,
+                                    field_name,
+                                });
                             }
                         } else {
-                            panic!("struct `{name}` not found in structures map");
+                            return Err(RuntimeError::UnknownStruct {
+                                program_counter: self.program_counter,
+                                struct_name: name,
+                            });
                         }
                     }
+                    Value::Nil => {
+                        return Err(RuntimeError::NilFieldAccess {
+                            program_counter: self.program_counter,
+                            field_name,
+                        });
+                    }
                     _ => {
-                        panic!("GetField expects a Struct or Struct Ref");
+                        return Err(self.type_mismatch("struct", struct_value));
                     }
                 }
             }
+            Instruction::NewEnum(enum_name, variant_name, fields_count) => {
+                let fields =
+                    self.stack
+                        .split_off(self.stack.len().checked_sub(fields_count).ok_or(
+                            RuntimeError::StackUnderflow {
+                                program_counter: self.program_counter,
+                            },
+                        )?);
+
+                let handle = self.allocate(Object::Enum {
+                    name: enum_name,
+                    variant: variant_name,
+                    fields,
+                })?;
+
+                self.stack.push(Value::Ref(handle));
+                self.maybe_run_gc();
+            }
+            Instruction::IsVariant(variant_name) => {
+                let enum_value = self.pop()?;
+                match enum_value {
+                    Value::Ref(handle) => {
+                        if let Object::Enum { variant, .. } = self.gc.get(handle) {
+                            self.stack.push(Value::Bool(*variant == variant_name));
+                        } else {
+                            return Err(self.type_mismatch("enum", Value::Ref(handle)));
+                        }
+                    }
+                    Value::Nil => {
+                        return Err(RuntimeError::NilMatch {
+                            program_counter: self.program_counter,
+                        });
+                    }
+                    _ => {
+                        return Err(self.type_mismatch("enum", enum_value));
+                    }
+                }
+            }
+            Instruction::GetVariantField(index) => {
+                let enum_value = self.pop()?;
+                if let Value::Ref(handle) = enum_value {
+                    if let Object::Enum { fields, .. } = self.gc.get(handle) {
+                        let value = fields
+                            .get(index)
+                            .ok_or(RuntimeError::MissingVariantField {
+                                program_counter: self.program_counter,
+                                index,
+                            })?
+                            .clone();
+                        self.stack.push(value);
+                    } else {
+                        return Err(self.type_mismatch("enum", Value::Ref(handle)));
+                    }
+                } else {
+                    return Err(self.type_mismatch("enum", enum_value));
+                }
+            }
             Instruction::Print => {
-                let top = self.stack.last().expect("stack underflow");
-                self.print_value(top);
+                let top = self.stack.last().ok_or(RuntimeError::StackUnderflow {
+                    program_counter: self.program_counter,
+                })?;
+                print!("{}", self.format_value(top));
             }
             Instruction::Println => {
-                let top = self.stack.last().expect("stack underflow");
+                let top = self.stack.last().ok_or(RuntimeError::StackUnderflow {
+                    program_counter: self.program_counter,
+                })?;
 
-                self.print_value(top);
-                println!();
-            }
-            Instruction::Func(_) | Instruction::EndFunc => {
-                panic!("function definition in main block");
+                println!("{}", self.format_value(top));
             }
             Instruction::Halt => {
-                return Some(RunCommunication::Finished);
+                return Ok(Some(RunCommunication::Finished));
             }
             Instruction::Backoff(reason) => {
-                return Some(RunCommunication::RequireHotswap(
-                    self.perform_backoff(&reason),
-                ));
+                return Err(RuntimeError::Backoff {
+                    program_counter: self.program_counter,
+                    reason,
+                });
+            }
+            Instruction::Panic => {
+                let message = self.pop()?;
+
+                return Ok(Some(RunCommunication::Panicked {
+                    message: self.get_string(message)?,
+                    location: self.current_location(),
+                    backtrace: self.backtrace(),
+                }));
+            }
+            Instruction::Exit => {
+                let code = self.pop()?;
+                let code = self.get_int(&code)?;
+
+                // NOTE: OS keeps only the low byte of the status, so e.g. 256 would mean success
+                let Ok(code) = u8::try_from(code) else {
+                    return Err(RuntimeError::ExitCodeOutOfRange {
+                        program_counter: self.program_counter,
+                        code,
+                    });
+                };
+
+                return Ok(Some(RunCommunication::Exited(code)));
             }
         }
 
         self.program_counter += 1;
 
-        None
+        Ok(None)
     }
 
     fn preprocess_bytecode(&mut self) -> usize {
@@ -692,9 +1086,11 @@ impl VM {
             }
 
             let code_to_optimize = self.input[start..end].to_vec();
-            let optimized_code = Optimizer::optimize_function(code_to_optimize.clone(), start);
+            let locations = self.locations[start..end].to_vec();
+            let (optimized_code, optimized_locations) =
+                Optimizer::optimize_function(code_to_optimize, locations, start);
 
-            self.replace_code_region(start, end - 1, optimized_code);
+            self.replace_code_region(start, end - 1, optimized_code, optimized_locations);
         }
     }
 
@@ -716,20 +1112,33 @@ impl VM {
         }
 
         let function_code = self.input[func_start..func_end].to_vec();
+        let function_locations = self.locations[func_start..func_end].to_vec();
         let loop_start = start - func_start;
         let loop_end = end - func_start;
 
-        let optimized_loop =
-            Optimizer::optimize_loop(function_code, loop_start, loop_end, func_start);
-        self.replace_code_region(start, end, optimized_loop);
+        let (optimized_loop, optimized_locations) = Optimizer::optimize_loop(
+            function_code,
+            function_locations,
+            loop_start,
+            loop_end,
+            func_start,
+        );
+        self.replace_code_region(start, end, optimized_loop, optimized_locations);
     }
 
-    fn replace_code_region(&mut self, start: usize, end: usize, new_code: Vec<Instruction>) {
+    fn replace_code_region(
+        &mut self,
+        start: usize,
+        end: usize,
+        new_code: Vec<Instruction>,
+        new_locations: Vec<Option<CodeLocation>>,
+    ) {
         let old_size = end - start + 1;
         let new_size = new_code.len();
         let size_diff = isize::try_from(new_size).unwrap() - isize::try_from(old_size).unwrap();
 
         self.input.splice(start..=end, new_code);
+        self.locations.splice(start..=end, new_locations);
 
         if self.program_counter > start {
             self.program_counter =
@@ -743,15 +1152,23 @@ impl VM {
             }
         }
 
+        for state in &mut self.backup_states {
+            if state.program_counter >= start {
+                state.program_counter =
+                    usize::try_from(isize::try_from(state.program_counter).unwrap() + size_diff)
+                        .unwrap();
+            }
+        }
+
         for i in 0..self.input.len() {
             match &mut self.input[i] {
                 Instruction::Jump(target)
                 | Instruction::JumpIfTrue(target)
-                | Instruction::JumpIfFalse(target) => {
-                    if *target > end {
-                        *target =
-                            usize::try_from(isize::try_from(*target).unwrap() + size_diff).unwrap();
-                    }
+                | Instruction::JumpIfFalse(target)
+                    if *target > end =>
+                {
+                    *target =
+                        usize::try_from(isize::try_from(*target).unwrap() + size_diff).unwrap();
                 }
                 _ => {}
             }
@@ -764,81 +1181,345 @@ impl VM {
         }
     }
 
-    fn perform_backoff(&mut self, reason: &str) -> EcoString {
-        match &self.backup_state {
-            Some(backup_state) => {
-                let call_instruction = match self.call_stack.pop() {
-                    Some(address) => self.input[address - 1].clone(),
-                    None => unreachable!(),
-                };
-                let Instruction::Call(name) = call_instruction else {
-                    unreachable!();
-                };
+    /// Rolls execution back to the call of the current function, so that it
+    /// can be hotswapped and called again with the same arguments
+    ///
+    /// Returns name of the function, or `None` if there is no call to roll
+    /// back to (backoff in `main()`)
+    pub fn perform_backoff(&mut self) -> Option<EcoString> {
+        self.perform_backoff_to(0)
+    }
 
-                self.program_counter = backup_state.program_counter;
-                self.stack.clone_from(&backup_state.stack);
-                let _ = self.environments_stack.pop();
+    /// Rolls execution back to the call of the function in the frame, indexed
+    /// as in [`VM::backtrace`], discarding the frames above it
+    ///
+    /// Heap objects are rolled back as well, if snapshots are enabled with
+    /// [`VM::set_heap_snapshots`]
+    ///
+    /// Returns name of the function, or `None` if there is no call to roll
+    /// back to (the frame is `main()` or does not exist)
+    pub fn perform_backoff_to(&mut self, frame: usize) -> Option<EcoString> {
+        if frame >= self.backup_states.len() {
+            return None;
+        }
 
-                self.backup_state = None;
+        let discarded = self.backup_states.len() - frame - 1;
+        let mut backup_state = None;
 
-                name
+        // NOTE: inner calls mutated objects later, so their snapshots go first
+        for mut state in self.backup_states.drain(discarded..).rev() {
+            for (index, object) in std::mem::take(&mut state.heap_snapshots) {
+                *self.gc.get_mut(Handle(index)) = object;
             }
-            None => panic!("cannot recover from: {reason}"),
+
+            backup_state = Some(state);
         }
+
+        let backup_state = backup_state?;
+
+        let Instruction::Call(function_name) = self.input[backup_state.program_counter].clone()
+        else {
+            unreachable!();
+        };
+
+        self.call_stack.truncate(self.call_stack.len() - frame - 1);
+        self.environments_stack
+            .truncate(backup_state.environments_height);
+        self.stack
+            .truncate(backup_state.stack_height - backup_state.popped_values.len());
+        self.stack
+            .extend(backup_state.popped_values.into_iter().rev());
+        self.program_counter = backup_state.program_counter;
+
+        Some(function_name)
+    }
+
+    /// Location of the awa code, that the current instruction was produced from
+    #[must_use]
+    pub fn current_location(&self) -> Option<CodeLocation> {
+        self.location_at(self.program_counter)
+    }
+
+    /// Location of the awa code, that the instruction at the address was produced from
+    #[must_use]
+    pub fn location_at(&self, address: usize) -> Option<CodeLocation> {
+        self.locations.get(address).copied().flatten()
+    }
+
+    /// Frames of the awa call stack, from the current function to `main()`
+    #[must_use]
+    pub fn backtrace(&self) -> Vec<StackFrame> {
+        // NOTE: return address of each call points right after its `Call` instruction
+        let addresses = std::iter::once(self.program_counter)
+            .chain(self.call_stack.iter().rev().map(|address| address - 1));
+
+        addresses
+            .zip(self.environments_stack.iter().rev())
+            .map(|(address, environment)| {
+                let mut locals = environment
+                    .iter()
+                    .filter(|(name, _)| !name.contains('#'))
+                    .map(|(name, value)| (name.clone(), self.format_value(value)))
+                    .collect::<Vec<_>>();
+                locals.sort();
+
+                StackFrame {
+                    function_name: self.get_function_name(address),
+                    location: self.location_at(address),
+                    locals,
+                }
+            })
+            .collect()
+    }
+
+    /// Name of the function, code at the address belongs to
+    fn get_function_name(&self, address: usize) -> EcoString {
+        self.input[..=address.min(self.input.len() - 1)]
+            .iter()
+            .rev()
+            .find_map(|instruction| match instruction {
+                Instruction::Func(name) => Some(name.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
     }
 
-    fn lookup_variable(&self, name: &EcoString) -> &Value {
+    fn lookup_variable(&self, name: &EcoString) -> Result<&Value, RuntimeError> {
         for environment in self.environments_stack.iter().rev() {
             if let Some(value) = environment.get(name) {
-                return value;
+                return Ok(value);
+            }
+        }
+
+        Err(RuntimeError::UndefinedVariable {
+            program_counter: self.program_counter,
+            variable_name: name.clone(),
+        })
+    }
+
+    fn pop(&mut self) -> Result<Value, RuntimeError> {
+        let value = self.stack.pop().ok_or(RuntimeError::StackUnderflow {
+            program_counter: self.program_counter,
+        })?;
+
+        // NOTE: arguments of the call are saved, as the caller has to push them again on backoff
+        if let Some(state) = self.backup_states.last_mut() {
+            if self.stack.len() < state.stack_height - state.popped_values.len() {
+                state.popped_values.push(value.clone());
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Saves the object, if the current call mutates it for the first time,
+    /// and it was allocated before the call
+    fn snapshot_object(&mut self, handle: Handle) {
+        if !self.should_snapshot_heap {
+            return;
+        }
+
+        let Some(state) = self.backup_states.last_mut() else {
+            return;
+        };
+
+        if handle.0 < state.heap_height && !state.heap_snapshots.contains_key(&handle.0) {
+            state
+                .heap_snapshots
+                .insert(handle.0, self.gc.get(handle).clone());
+        }
+    }
+
+    /// Drops saved state of the returned call, passing its snapshots to the
+    /// caller, as rolling back the caller has to undo mutations of the callee too
+    fn discard_backup_state(&mut self) {
+        let Some(state) = self.backup_states.pop() else {
+            return;
+        };
+
+        if let Some(caller_state) = self.backup_states.last_mut() {
+            for (index, object) in state.heap_snapshots {
+                if index < caller_state.heap_height {
+                    caller_state.heap_snapshots.entry(index).or_insert(object);
+                }
+            }
+        }
+    }
+
+    fn integer_overflow(&self, expression: String) -> RuntimeError {
+        RuntimeError::IntegerOverflow {
+            program_counter: self.program_counter,
+            expression: expression.into(),
+        }
+    }
+
+    fn type_mismatch(&self, expected: &str, found: Value) -> RuntimeError {
+        RuntimeError::TypeMismatch {
+            program_counter: self.program_counter,
+            expected: expected.into(),
+            found,
+        }
+    }
+
+    /// Allocates the object on the heap. Garbage is collected first, if the
+    /// heap is full, so that only live objects can exceed its limit
+    fn allocate(&mut self, mut object: Object) -> Result<Handle, RuntimeError> {
+        if self.gc.is_full() {
+            self.collect_garbage_around(&mut object);
+        }
+
+        self.gc
+            .allocate(object)
+            .map_err(|HeapLimitReached| RuntimeError::HeapLimitExceeded {
+                program_counter: self.program_counter,
+                limit: self.gc.max_heap_size.unwrap_or_default(),
+            })
+    }
+
+    /// Creates new instance of the struct out of its template
+    ///
+    /// `instantiated_structs` are the structs, that are being created around
+    /// it, so that struct, which holds itself, does not recurse forever
+    fn instantiate_struct(
+        &mut self,
+        struct_name: &EcoString,
+        instantiated_structs: &mut Vec<EcoString>,
+    ) -> Result<Value, RuntimeError> {
+        let Some(template) = self.structures.get(struct_name).cloned() else {
+            return Ok(Value::Nil);
+        };
+        if instantiated_structs.contains(struct_name) {
+            return Ok(Value::Nil);
+        }
+        instantiated_structs.push(struct_name.clone());
+
+        // NOTE: finished fields are kept on the stack, so that they survive
+        // collection, caused by allocation of the next ones
+        let stack_height = self.stack.len();
+        for value in template.values() {
+            match self.instantiate_value(value, instantiated_structs) {
+                Ok(value) => self.stack.push(value),
+                Err(error) => {
+                    self.stack.truncate(stack_height);
+                    return Err(error);
+                }
             }
         }
-        panic!("stack underflow")
+        let fields = template
+            .keys()
+            .cloned()
+            .zip(self.stack.drain(stack_height..))
+            .collect();
+
+        instantiated_structs.pop();
+
+        let handle = self.allocate(Object::Struct {
+            name: struct_name.clone(),
+            fields,
+        })?;
+
+        Ok(Value::Ref(handle))
     }
 
+    /// Creates value of the field out of its template value: arrays and
+    /// structs are allocated on the heap, so that each instance has its own
+    fn instantiate_value(
+        &mut self,
+        template: &Value,
+        instantiated_structs: &mut Vec<EcoString>,
+    ) -> Result<Value, RuntimeError> {
+        match template {
+            Value::Slice(elements) => {
+                let handle = self.allocate(Object::Array(elements.clone()))?;
+
+                Ok(Value::Ref(handle))
+            }
+            Value::Struct { name, .. } => self.instantiate_struct(name, instantiated_structs),
+            _ => Ok(template.clone()),
+        }
+    }
+
+    /// Collects garbage, if enough objects were allocated since the last
+    /// collection, or if the heap is full
     fn maybe_run_gc(&mut self) {
-        if self.gc.alloc_count > self.gc.threshold {
-            self.gc
-                .collect_garbage(&mut self.stack, &mut self.environments_stack);
+        if self.gc.alloc_count > self.gc.threshold || self.gc.is_full() {
+            self.collect_garbage();
+        }
+    }
+
+    fn collect_garbage(&mut self) {
+        self.gc.collect_garbage(
+            &mut self.stack,
+            &mut self.environments_stack,
+            &mut self.backup_states,
+        );
+    }
+
+    /// Collects garbage before the object is allocated, keeping the values,
+    /// it holds, alive on the stack meanwhile
+    fn collect_garbage_around(&mut self, object: &mut Object) {
+        let stack_height = self.stack.len();
+
+        match object {
+            Object::String(_) => self.collect_garbage(),
+            Object::Array(values) | Object::Enum { fields: values, .. } => {
+                self.stack.append(values);
+                self.collect_garbage();
+                values.extend(self.stack.drain(stack_height..));
+            }
+            Object::Struct { fields, .. } => {
+                let (names, values): (Vec<_>, Vec<_>) = fields.drain().unzip();
+
+                self.stack.extend(values);
+                self.collect_garbage();
+                fields.extend(names.into_iter().zip(self.stack.drain(stack_height..)));
+            }
         }
     }
 
-    fn get_int(value: &Value) -> i64 {
+    fn get_int(&self, value: &Value) -> Result<i64, RuntimeError> {
         match value {
-            Value::Int(int) => *int,
-            Value::Ref(_) => panic!("expected Int, found Ref"),
-            _ => panic!("expected Int"),
+            Value::Int(int) => Ok(*int),
+            _ => Err(self.type_mismatch("int", value.clone())),
         }
     }
 
-    fn get_float(value: &Value) -> f64 {
+    fn get_bool(&self, value: &Value) -> Result<bool, RuntimeError> {
         match value {
-            Value::Float(float) => *float,
-            Value::Ref(_) => panic!("expected Float, found Ref"),
-            _ => panic!("expected Float"),
+            Value::Bool(bool) => Ok(*bool),
+            _ => Err(self.type_mismatch("bool", value.clone())),
         }
     }
 
-    fn get_string(&self, value: Value) -> EcoString {
+    fn get_float(&self, value: &Value) -> Result<f64, RuntimeError> {
         match value {
-            Value::String(string) => string,
+            Value::Float(float) => Ok(*float),
+            _ => Err(self.type_mismatch("float", value.clone())),
+        }
+    }
+
+    fn get_string(&self, value: Value) -> Result<EcoString, RuntimeError> {
+        match value {
+            Value::String(string) => Ok(string),
             Value::Ref(handle) => match self.gc.get(handle) {
-                Object::String(string) => string.clone(),
-                _ => panic!("expected String object"),
+                Object::String(string) => Ok(string.clone()),
+                _ => Err(self.type_mismatch("string", value)),
             },
-            _ => panic!("expected String"),
+            _ => Err(self.type_mismatch("string", value)),
         }
     }
 
-    fn is_equal_values(&self, lhs: Value, rhs: Value) -> bool {
-        match (lhs, rhs) {
+    fn is_equal_values(&self, lhs: Value, rhs: Value) -> Result<bool, RuntimeError> {
+        let equal = match (lhs, rhs) {
             (Value::Int(lhs), Value::Int(rhs)) => lhs == rhs,
             (Value::Float(lhs), Value::Float(rhs)) => (lhs - rhs).abs() < f64::EPSILON,
             (Value::Char(lhs), Value::Char(rhs)) => lhs == rhs,
+            (Value::Bool(lhs), Value::Bool(rhs)) => lhs == rhs,
+            (Value::Nil, Value::Nil) => true,
             (Value::String(lhs), Value::String(rhs)) => lhs == rhs,
-            (Value::Ref(lhs), Value::Ref(rhs)) => {
-                let lhs = self.gc.get(lhs);
-                let rhs = self.gc.get(rhs);
+            (Value::Ref(lhs_handle), Value::Ref(rhs_handle)) => {
+                let lhs = self.gc.get(lhs_handle);
+                let rhs = self.gc.get(rhs_handle);
 
                 match (lhs, rhs) {
                     (Object::String(lhs), Object::String(rhs)) => lhs == rhs,
@@ -853,11 +1534,44 @@ impl VM {
                             fields: fields2,
                         },
                     ) => name1 == name2 && fields1 == fields2,
-                    _ => panic!("non comparable: {lhs:?}, {rhs:?}"),
+                    (
+                        Object::Enum {
+                            name: name1,
+                            variant: variant1,
+                            fields: fields1,
+                        },
+                        Object::Enum {
+                            name: name2,
+                            variant: variant2,
+                            fields: fields2,
+                        },
+                    ) => {
+                        if name1 != name2 || variant1 != variant2 || fields1.len() != fields2.len()
+                        {
+                            return Ok(false);
+                        }
+
+                        for (lhs, rhs) in fields1.iter().zip(fields2) {
+                            if !self.is_equal_values(lhs.clone(), rhs.clone())? {
+                                return Ok(false);
+                            }
+                        }
+
+                        true
+                    }
+                    _ => {
+                        return Err(RuntimeError::NotComparable {
+                            program_counter: self.program_counter,
+                            lhs: lhs_handle,
+                            rhs: rhs_handle,
+                        })
+                    }
                 }
             }
             _ => false,
-        }
+        };
+
+        Ok(equal)
     }
 
     fn is_true(value: Value) -> bool {
@@ -865,6 +1579,7 @@ impl VM {
             Value::Int(int) => int != 0,
             Value::Float(float) => float != 0.0,
             Value::Char(char) => char != '\0',
+            Value::Bool(bool) => bool,
             Value::String(string) => !string.is_empty(),
             Value::Slice(slice) => !slice.is_empty(),
             Value::Struct { .. } | Value::Ref(_) => true,
@@ -872,161 +1587,417 @@ impl VM {
         }
     }
 
-    fn print_value(&self, value: &Value) {
+    fn format_value(&self, value: &Value) -> String {
+        let mut output = String::new();
+        self.write_value(&mut output, value, &mut Vec::new());
+
+        output
+    }
+
+    /// `objects` are the ones being written, so that cyclic references are
+    /// written as `...` instead of endlessly
+    fn write_value(&self, output: &mut String, value: &Value, objects: &mut Vec<Handle>) {
         match value {
-            Value::Int(int) => print!("{int}"),
-            Value::Float(float) => print!("{float}"),
-            Value::Char(char) => print!("{char}"),
-            Value::String(string) => print!("{string}"),
+            Value::Int(int) => {
+                let _ = write!(output, "{int}");
+            }
+            Value::Float(float) => {
+                let _ = write!(output, "{float}");
+            }
+            Value::Char(char) => {
+                let _ = write!(output, "{char}");
+            }
+            Value::Bool(bool) => {
+                let _ = write!(output, "{bool}");
+            }
+            Value::String(string) => {
+                let _ = write!(output, "{string}");
+            }
             Value::Slice(arr) => {
-                print!("[");
+                let _ = write!(output, "[");
 
                 for (i, value) in arr.iter().enumerate() {
                     if i > 0 {
-                        print!(", ");
+                        let _ = write!(output, ", ");
                     }
 
-                    self.print_value(value);
+                    self.write_value(output, value, objects);
                 }
 
-                print!("]");
+                let _ = write!(output, "]");
             }
             Value::Struct { name, fields } => {
-                print!("Struct {name} {{");
+                let _ = write!(output, "Struct {name} {{");
                 let mut first = true;
 
                 for (name, val) in fields {
                     if !first {
-                        print!(", ");
+                        let _ = write!(output, ", ");
                     }
-                    print!("{name}: ");
-                    self.print_value(val);
+                    let _ = write!(output, "{name}: ");
+                    self.write_value(output, val, objects);
                     first = false;
                 }
 
-                print!("}}");
+                let _ = write!(output, "}}");
             }
             Value::Ref(handle) => {
+                if objects.contains(handle) {
+                    let _ = write!(output, "...");
+                    return;
+                }
+
+                objects.push(*handle);
                 let object = self.gc.get(*handle);
 
                 match object {
-                    Object::String(string) => print!("{string}"),
+                    Object::String(string) => {
+                        let _ = write!(output, "{string}");
+                    }
                     Object::Array(array) => {
-                        print!("[");
+                        let _ = write!(output, "[");
                         for (i, value) in array.iter().enumerate() {
                             if i > 0 {
-                                print!(", ");
+                                let _ = write!(output, ", ");
                             }
 
-                            self.print_value(value);
+                            self.write_value(output, value, objects);
                         }
 
-                        print!("]");
+                        let _ = write!(output, "]");
                     }
                     Object::Struct { name, fields } => {
-                        print!("Struct {name} {{");
+                        let _ = write!(output, "Struct {name} {{");
                         let mut first = true;
 
                         for (name, val) in fields {
                             if !first {
-                                print!(", ");
+                                let _ = write!(output, ", ");
                             }
-                            print!("{name}: ");
-                            self.print_value(val);
+                            let _ = write!(output, "{name}: ");
+                            self.write_value(output, val, objects);
                             first = false;
                         }
-                        print!("}}");
+                        let _ = write!(output, "}}");
+                    }
+                    Object::Enum {
+                        name,
+                        variant,
+                        fields,
+                    } => {
+                        let _ = write!(output, "{name}::{variant}");
+
+                        if !fields.is_empty() {
+                            let _ = write!(output, "(");
+                            for (i, value) in fields.iter().enumerate() {
+                                if i > 0 {
+                                    let _ = write!(output, ", ");
+                                }
+
+                                self.write_value(output, value, objects);
+                            }
+                            let _ = write!(output, ")");
+                        }
                     }
                 }
+
+                objects.pop();
+            }
+            Value::Nil => {
+                let _ = write!(output, "nil");
             }
-            Value::Nil => print!("nil"),
         }
     }
 
-    /// Perform function hotswap
+    /// Perform hotswap of all structs in the new fragment
     ///
-    /// # Panics
+    /// Replaces `structures` templates, then migrates live instances of
+    /// the structs on the heap, along with their snapshots: new fields get
+    /// default values, removed fields are dropped. Fields, present in both
+    /// versions, are expected to keep their types
     ///
-    /// Will panic if the function to be swapped out is not present in the code
+    /// New array and struct fields get their own objects on the heap, as
+    /// in instances, created after the hotswap
     ///
-    /// 1. Finds `Func(name)` ... `EndFunc` in the new fragment.
-    /// 2. Adjusts `Jump`/`JumpIfTrue`/`JumpIfFalse` by an offset equal to the current length of `self.input`.
-    /// 3. Adds to `self.input`: `Func(name)`, [body], `EndFunc`.
-    /// 4. Updates `functions[name]` to point to the start of the inserted body.
-    pub fn hotswap_function(&mut self, new_code: &[Instruction]) {
-        let (function_name, body) = VM::extract_func_block(new_code);
-        let offset = self.input.len();
-        let binding = self.functions.clone();
-        let function_start = binding.get(&function_name).expect("no function found");
-        let mut function_end = *function_start;
-
-        while function_end > self.input.len() {
-            match self.input.get(function_end) {
-                Some(Instruction::EndFunc) => {
-                    break;
+    /// Should be done along with `hotswap_functions` of the same fragment, so
+    /// that functions, which use new fields, never see the old instances
+    ///
+    /// # Errors
+    ///
+    /// Returns `RuntimeError::HeapLimitExceeded`, if there is no room on
+    /// the heap for the new fields even after garbage collection. Nothing
+    /// is migrated then
+    pub fn hotswap_structs(&mut self, new_code: &[Instruction]) -> Result<(), RuntimeError> {
+        let structures = VM::extract_struct_blocks(new_code);
+
+        if structures.is_empty() {
+            return Ok(());
+        }
+
+        // NOTE: failed hotswap leaves the program unchanged, so room for the
+        // new field objects is made before any instance is migrated
+        if !self.has_room_for(self.count_migration_objects(&structures)) {
+            self.collect_garbage();
+
+            if !self.has_room_for(self.count_migration_objects(&structures)) {
+                return Err(RuntimeError::HeapLimitExceeded {
+                    program_counter: self.program_counter,
+                    limit: self.gc.max_heap_size.unwrap_or_default(),
+                });
+            }
+        }
+
+        // NOTE: objects are allocated after the heap is walked, each one is
+        // identified by index of the backup state, that has it as a snapshot
+        let mut allocated_fields = Vec::new();
+
+        let heap = self
+            .gc
+            .heap
+            .iter_mut()
+            .enumerate()
+            .map(|(index, object)| (None, index, object));
+        let snapshots =
+            self.backup_states
+                .iter_mut()
+                .enumerate()
+                .flat_map(|(state, backup_state)| {
+                    backup_state
+                        .heap_snapshots
+                        .iter_mut()
+                        .map(move |(&index, object)| (Some(state), index, object))
+                });
+
+        for (state, index, object) in heap.chain(snapshots) {
+            let Object::Struct { name, fields } = object else {
+                continue;
+            };
+            let Some(template) = structures.get(name) else {
+                continue;
+            };
+
+            fields.retain(|field_name, _| template.contains_key(field_name));
+
+            for (field_name, value) in template {
+                if fields.contains_key(field_name) {
+                    continue;
                 }
-                _ => {
-                    function_end += 1;
+
+                if let Value::Slice(_) | Value::Struct { .. } = value {
+                    allocated_fields.push((state, index, field_name.clone(), value.clone()));
                 }
+                fields.insert(field_name.clone(), value.clone());
             }
         }
 
-        let body_fixed = VM::adjust_jumps(body, offset);
+        self.structures.extend(structures);
 
-        self.input.push(Instruction::Func(function_name.clone()));
-        let start_address = self.input.len();
+        for (state, index, field_name, template) in allocated_fields {
+            let value = self.instantiate_value(&template, &mut Vec::new())?;
 
-        for instruction in body_fixed {
-            self.input.push(instruction);
+            let object = match state {
+                Some(state) => self.backup_states[state].heap_snapshots.get_mut(&index),
+                None => self.gc.heap.get_mut(index),
+            };
+            if let Some(Object::Struct { fields, .. }) = object {
+                fields.insert(field_name, value);
+            }
         }
 
-        self.input.push(Instruction::EndFunc);
-        self.functions.insert(function_name.clone(), start_address);
-        self.execution_stats
-            .optimized_functions
-            .remove(&function_name);
-        self.execution_stats
-            .loop_last_optimization
-            .retain(|&address, _| address < function_end && address > *function_start);
+        Ok(())
     }
 
-    fn extract_func_block(code: &[Instruction]) -> (EcoString, Vec<Instruction>) {
-        let mut func_name = EcoString::new();
-        let mut start = None;
-        let mut end = None;
+    /// Amount of objects, that migration of live instances to the new
+    /// `structures` allocates for their added array and struct fields
+    fn count_migration_objects(
+        &self,
+        structures: &HashMap<EcoString, HashMap<EcoString, Value>>,
+    ) -> usize {
+        let snapshots = self
+            .backup_states
+            .iter()
+            .flat_map(|backup_state| backup_state.heap_snapshots.values());
+        let mut count = 0;
+
+        for object in self.gc.heap.iter().chain(snapshots) {
+            let Object::Struct { name, fields } = object else {
+                continue;
+            };
+            let Some(template) = structures.get(name) else {
+                continue;
+            };
+
+            for (field_name, value) in template {
+                if !fields.contains_key(field_name) {
+                    count += self.count_instance_objects(value, structures, &mut Vec::new());
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Amount of objects, that `instantiate_value` allocates for the template
+    /// value, once `structures` are hotswapped
+    fn count_instance_objects(
+        &self,
+        template: &Value,
+        structures: &HashMap<EcoString, HashMap<EcoString, Value>>,
+        counted_structs: &mut Vec<EcoString>,
+    ) -> usize {
+        match template {
+            Value::Slice(_) => 1,
+            Value::Struct { name, .. } => {
+                let Some(struct_template) =
+                    structures.get(name).or_else(|| self.structures.get(name))
+                else {
+                    return 0;
+                };
+                if counted_structs.contains(name) {
+                    return 0;
+                }
+                counted_structs.push(name.clone());
+
+                let mut count = 1;
+                for value in struct_template.values() {
+                    count += self.count_instance_objects(value, structures, counted_structs);
+                }
+
+                counted_structs.pop();
+                count
+            }
+            _ => 0,
+        }
+    }
+
+    /// Whether the heap can take the amount of objects without exceeding its limit
+    fn has_room_for(&self, objects: usize) -> bool {
+        self.gc
+            .max_heap_size
+            .is_none_or(|max_heap_size| self.gc.heap.len() + objects <= max_heap_size)
+    }
+
+    /// Perform hotswap of all functions in the new fragment at once
+    ///
+    /// 1. Finds each `Func(name)` ... `EndFunc` in the new fragment.
+    /// 2. Adds to `self.input`: `Func(name)`, [body], `EndFunc`, adjusting
+    ///    `Jump`/`JumpIfTrue`/`JumpIfFalse` to the new place of the body.
+    /// 3. Updates `functions[name]` to point to the start of the inserted body,
+    ///    adding the functions, that are not present in the code yet.
+    ///
+    /// As nothing runs in between, the next instruction sees either none or all
+    /// of the new functions
+    ///
+    /// `new_locations` are locations of `new_code` instructions in the hotswapped
+    /// source, which gets the next source index
+    pub fn hotswap_functions(
+        &mut self,
+        new_code: &[Instruction],
+        new_locations: &[Option<Location>],
+    ) {
+        let blocks = VM::extract_func_blocks(new_code);
+        let source = self.sources_count;
+        self.sources_count += 1;
+
+        for (function_name, body_range) in blocks {
+            // NOTE: loops of the replaced function are not going to be optimized anymore
+            if let Some(&function_start) = self.functions.get(&function_name) {
+                let function_end = self.input[function_start..]
+                    .iter()
+                    .position(|instruction| matches!(instruction, Instruction::EndFunc))
+                    .map_or(self.input.len(), |length| function_start + length);
+
+                self.execution_stats
+                    .loop_last_optimization
+                    .retain(|address, _| !(function_start..function_end).contains(address));
+            }
+
+            self.input.push(Instruction::Func(function_name.clone()));
+            self.locations.push(None);
+            let start_address = self.input.len();
+
+            let body = new_code[body_range.clone()].to_vec();
+            self.input
+                .extend(VM::adjust_jumps(body, body_range.start, start_address));
+            self.locations.extend(body_range.map(|index| {
+                new_locations
+                    .get(index)
+                    .copied()
+                    .flatten()
+                    .map(|location| CodeLocation { source, location })
+            }));
+
+            self.input.push(Instruction::EndFunc);
+            self.locations.push(None);
+            self.functions.insert(function_name.clone(), start_address);
+            self.execution_stats
+                .optimized_functions
+                .remove(&function_name);
+        }
+    }
+
+    /// Name and body of each `Func(name)` ... `EndFunc` block in the code
+    fn extract_func_blocks(code: &[Instruction]) -> Vec<(EcoString, std::ops::Range<usize>)> {
+        let mut blocks = Vec::new();
+        let mut current = None;
 
         for (i, instruction) in code.iter().enumerate() {
             match instruction {
                 Instruction::Func(name) => {
-                    func_name = name.clone();
-                    start = Some(i);
+                    current = Some((name.clone(), i + 1));
                 }
                 Instruction::EndFunc => {
-                    if start.is_some() && end.is_none() {
-                        end = Some(i);
-                        break;
+                    if let Some((name, start)) = current.take() {
+                        blocks.push((name, start..i));
                     }
                 }
                 _ => {}
             }
         }
 
-        let start = start.expect("no Func(...) in new_code");
-        let end = end.expect("no EndFunc after Func(...)");
+        blocks
+    }
+
+    /// Fields of each `Struct(name)` ... `EndStruct` block in the code, with
+    /// their default values
+    fn extract_struct_blocks(
+        code: &[Instruction],
+    ) -> HashMap<EcoString, HashMap<EcoString, Value>> {
+        let mut structures = HashMap::new();
+        let mut current = None;
 
-        let body = code[start + 1..end].to_vec();
+        for instruction in code {
+            match instruction {
+                Instruction::Struct(name) => {
+                    current = Some((name.clone(), HashMap::new()));
+                }
+                Instruction::Field(field_name, value) => {
+                    if let Some((_, fields)) = current.as_mut() {
+                        fields.insert(field_name.clone(), value.clone());
+                    }
+                }
+                Instruction::EndStruct => {
+                    if let Some((name, fields)) = current.take() {
+                        structures.insert(name, fields);
+                    }
+                }
+                _ => {}
+            }
+        }
 
-        (func_name, body)
+        structures
     }
 
-    fn adjust_jumps(body: Vec<Instruction>, offset: usize) -> Vec<Instruction> {
+    /// Move jumps of the body, so that they target its instructions, after
+    /// the instruction at `from` is placed at `to`
+    fn adjust_jumps(body: Vec<Instruction>, from: usize, to: usize) -> Vec<Instruction> {
         let mut result = Vec::with_capacity(body.len());
 
         for instruction in body {
             let instruction = match instruction {
-                Instruction::Jump(index) => Instruction::Jump(index + offset),
-                Instruction::JumpIfTrue(index) => Instruction::JumpIfTrue(index + offset),
-                Instruction::JumpIfFalse(index) => Instruction::JumpIfFalse(index + offset),
+                Instruction::Jump(index) => Instruction::Jump(index + to - from),
+                Instruction::JumpIfTrue(index) => Instruction::JumpIfTrue(index + to - from),
+                Instruction::JumpIfFalse(index) => Instruction::JumpIfFalse(index + to - from),
                 other => other,
             };
 
@@ -0,0 +1,255 @@
+use ecow::EcoString;
+
+use super::instruction::{Handle, Instruction, Value};
+
+/// Fault of the running awa code
+///
+/// Each variant carries program counter of the failed instruction
+#[derive(Debug, PartialEq, Clone)]
+pub enum RuntimeError {
+    /// Explicit backoff, e.g. reached `todo`
+    Backoff {
+        program_counter: usize,
+        reason: EcoString,
+    },
+    DivisionByZero {
+        program_counter: usize,
+        dividend: Value,
+    },
+    ModuloByZero {
+        program_counter: usize,
+        dividend: i64,
+    },
+    /// Result of the integer operation does not fit into `int`
+    IntegerOverflow {
+        program_counter: usize,
+        expression: EcoString,
+    },
+    ShiftOutOfRange {
+        program_counter: usize,
+        amount: i64,
+    },
+    IndexOutOfRange {
+        program_counter: usize,
+        index: i64,
+        length: usize,
+    },
+    PopFromEmptyArray {
+        program_counter: usize,
+    },
+    /// Code passed to `exit` is not a valid process exit status
+    ExitCodeOutOfRange {
+        program_counter: usize,
+        code: i64,
+    },
+    NilFieldAccess {
+        program_counter: usize,
+        field_name: EcoString,
+    },
+    NilMatch {
+        program_counter: usize,
+    },
+    MissingField {
+        program_counter: usize,
+        struct_name: EcoString,
+        field_name: EcoString,
+    },
+    MissingVariantField {
+        program_counter: usize,
+        index: usize,
+    },
+    UnknownStruct {
+        program_counter: usize,
+        struct_name: EcoString,
+    },
+    UndefinedFunction {
+        program_counter: usize,
+        function_name: EcoString,
+    },
+    UndefinedVariable {
+        program_counter: usize,
+        variable_name: EcoString,
+    },
+    StackUnderflow {
+        program_counter: usize,
+    },
+    /// Call would exceed maximum call depth of the VM
+    StackOverflow {
+        program_counter: usize,
+        depth: usize,
+    },
+    TypeMismatch {
+        program_counter: usize,
+        expected: EcoString,
+        found: Value,
+    },
+    /// Heap objects of different kinds
+    NotComparable {
+        program_counter: usize,
+        lhs: Handle,
+        rhs: Handle,
+    },
+    UnexpectedInstruction {
+        program_counter: usize,
+        instruction: Instruction,
+    },
+    /// Instruction budget of the VM is exhausted
+    OutOfFuel {
+        program_counter: usize,
+    },
+    /// Heap is full of live objects, so the new one cannot be allocated
+    HeapLimitExceeded {
+        program_counter: usize,
+        limit: usize,
+    },
+}
+
+impl RuntimeError {
+    #[must_use]
+    pub fn get_program_counter(&self) -> usize {
+        match self {
+            RuntimeError::Backoff {
+                program_counter, ..
+            }
+            | RuntimeError::DivisionByZero {
+                program_counter, ..
+            }
+            | RuntimeError::ModuloByZero {
+                program_counter, ..
+            }
+            | RuntimeError::IntegerOverflow {
+                program_counter, ..
+            }
+            | RuntimeError::ShiftOutOfRange {
+                program_counter, ..
+            }
+            | RuntimeError::IndexOutOfRange {
+                program_counter, ..
+            }
+            | RuntimeError::PopFromEmptyArray { program_counter }
+            | RuntimeError::ExitCodeOutOfRange {
+                program_counter, ..
+            }
+            | RuntimeError::NilFieldAccess {
+                program_counter, ..
+            }
+            | RuntimeError::NilMatch { program_counter }
+            | RuntimeError::MissingField {
+                program_counter, ..
+            }
+            | RuntimeError::MissingVariantField {
+                program_counter, ..
+            }
+            | RuntimeError::UnknownStruct {
+                program_counter, ..
+            }
+            | RuntimeError::UndefinedFunction {
+                program_counter, ..
+            }
+            | RuntimeError::UndefinedVariable {
+                program_counter, ..
+            }
+            | RuntimeError::StackUnderflow { program_counter }
+            | RuntimeError::StackOverflow {
+                program_counter, ..
+            }
+            | RuntimeError::TypeMismatch {
+                program_counter, ..
+            }
+            | RuntimeError::NotComparable {
+                program_counter, ..
+            }
+            | RuntimeError::UnexpectedInstruction {
+                program_counter, ..
+            }
+            | RuntimeError::OutOfFuel { program_counter }
+            | RuntimeError::HeapLimitExceeded {
+                program_counter, ..
+            } => *program_counter,
+        }
+    }
+
+    /// Whether execution can be rolled back to the call of the failed
+    /// function, so that it could be hotswapped
+    ///
+    /// Faults of the awa code are recoverable. The rest mean, that state of
+    /// the VM is broken, and there is nothing to roll back to, or that
+    /// resource limits of the run are exhausted
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            RuntimeError::StackUnderflow { .. }
+                | RuntimeError::TypeMismatch { .. }
+                | RuntimeError::NotComparable { .. }
+                | RuntimeError::UndefinedVariable { .. }
+                | RuntimeError::UnexpectedInstruction { .. }
+                | RuntimeError::OutOfFuel { .. }
+                | RuntimeError::HeapLimitExceeded { .. }
+        )
+    }
+
+    #[must_use]
+    pub fn get_description(&self) -> String {
+        match self {
+            RuntimeError::Backoff { reason, .. } => reason.to_string(),
+            RuntimeError::DivisionByZero {
+                dividend: Value::Float(_),
+                ..
+            } => "floating point division by zero".to_owned(),
+            RuntimeError::DivisionByZero { .. } => "integer division by zero".to_owned(),
+            RuntimeError::ModuloByZero { .. } => "modulo by zero".to_owned(),
+            RuntimeError::IntegerOverflow { expression, .. } => {
+                format!("integer overflow in `{expression}`")
+            }
+            RuntimeError::ShiftOutOfRange { amount, .. } => {
+                format!("shift amount {amount} is out of range")
+            }
+            RuntimeError::IndexOutOfRange { index, length, .. } => {
+                format!("index {index} is out of range for array of length {length}")
+            }
+            RuntimeError::PopFromEmptyArray { .. } => "pop from empty array".to_owned(),
+            RuntimeError::ExitCodeOutOfRange { code, .. } => {
+                format!("exit code {code} is out of range 0..=255")
+            }
+            RuntimeError::NilFieldAccess { field_name, .. } => {
+                format!("access to field `{field_name}` of nil value")
+            }
+            RuntimeError::NilMatch { .. } => "match on nil value".to_owned(),
+            RuntimeError::MissingField {
+                struct_name,
+                field_name,
+                ..
+            } => format!("struct `{struct_name}` has no field `{field_name}`"),
+            RuntimeError::MissingVariantField { index, .. } => {
+                format!("variant has no field with index {index}")
+            }
+            RuntimeError::UnknownStruct { struct_name, .. } => {
+                format!("unknown struct `{struct_name}`")
+            }
+            RuntimeError::UndefinedFunction { function_name, .. } => {
+                format!("call to undefined function `{function_name}`")
+            }
+            RuntimeError::UndefinedVariable { variable_name, .. } => {
+                format!("undefined variable `{variable_name}`")
+            }
+            RuntimeError::StackUnderflow { .. } => "stack underflow".to_owned(),
+            RuntimeError::StackOverflow { depth, .. } => {
+                format!("stack overflow: maximum call depth of {depth} is exceeded")
+            }
+            RuntimeError::TypeMismatch {
+                expected, found, ..
+            } => format!("expected {expected}, found {found:?}"),
+            RuntimeError::NotComparable { lhs, rhs, .. } => {
+                format!("values are not comparable: {lhs:?}, {rhs:?}")
+            }
+            RuntimeError::UnexpectedInstruction { instruction, .. } => {
+                format!("unexpected instruction: {instruction:?}")
+            }
+            RuntimeError::OutOfFuel { .. } => "instruction limit is exhausted".to_owned(),
+            RuntimeError::HeapLimitExceeded { limit, .. } => {
+                format!("heap limit of {limit} objects is exceeded")
+            }
+        }
+    }
+}
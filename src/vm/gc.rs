@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 
-use super::instruction::{Handle, Value};
+use super::{
+    instruction::{Handle, Value},
+    State,
+};
 use ecow::EcoString;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Object {
     String(EcoString),
     Array(Vec<Value>),
@@ -11,13 +14,24 @@ pub enum Object {
         name: EcoString,
         fields: HashMap<EcoString, Value>,
     },
+    Enum {
+        name: EcoString,
+        variant: EcoString,
+        fields: Vec<Value>,
+    },
 }
 
+/// Allocation failed, as the heap already holds maximum amount of objects
+#[derive(Debug)]
+pub struct HeapLimitReached;
+
 pub struct GC {
     pub heap: Vec<Object>,
     pub marked: Vec<bool>,
     pub alloc_count: usize,
     pub threshold: usize,
+    /// Maximum amount of objects on the heap, unlimited if `None`
+    pub max_heap_size: Option<usize>,
     object_pool: ObjectPool,
     mark_stack: Vec<Handle>,
 }
@@ -67,15 +81,20 @@ impl GC {
             marked: Vec::with_capacity(100_000),
             alloc_count: 0,
             threshold: 1000,
+            max_heap_size: None,
             object_pool: ObjectPool::new(),
             mark_stack: Vec::with_capacity(100_000),
         }
     }
 
-    pub fn allocate(&mut self, object: Object) -> Handle {
+    pub fn allocate(&mut self, object: Object) -> Result<Handle, HeapLimitReached> {
         let index = self.heap.len();
         assert!(index != usize::MAX, "GC heap overflow");
 
+        if self.is_full() {
+            return Err(HeapLimitReached);
+        }
+
         let reused_object = match object {
             Object::String(string) => {
                 let mut pooled = self.object_pool.get_string();
@@ -100,13 +119,21 @@ impl GC {
                     fields: pooled,
                 }
             }
+            enum_object @ Object::Enum { .. } => enum_object,
         };
 
         self.heap.push(reused_object);
         self.marked.push(false);
         self.alloc_count += 1;
 
-        Handle(index)
+        Ok(Handle(index))
+    }
+
+    /// Whether the heap holds maximum amount of objects, so garbage has to be
+    /// collected before the next allocation
+    pub fn is_full(&self) -> bool {
+        self.max_heap_size
+            .is_some_and(|max_heap_size| self.heap.len() >= max_heap_size)
     }
 
     pub fn get(&self, handle: Handle) -> &Object {
@@ -121,6 +148,7 @@ impl GC {
         &mut self,
         stack: &mut [Value],
         environments_stack: &mut [HashMap<EcoString, Value>],
+        backup_states: &mut [State],
     ) {
         self.marked.clear();
         self.marked.resize(self.heap.len(), false);
@@ -135,8 +163,28 @@ impl GC {
             }
         }
 
+        // NOTE: backoff brings popped values and snapshots back, so they are alive until then
+        for state in backup_states.iter() {
+            for value in &state.popped_values {
+                self.mark_value(value);
+            }
+
+            for (&index, object) in &state.heap_snapshots {
+                self.mark_object(Handle(index));
+                self.mark_object_contents(object);
+            }
+        }
+
         let remap = self.compact();
 
+        // NOTE: compaction keeps order of objects, so the survivors below the height stay below it
+        let mut survivors_before = Vec::with_capacity(remap.len() + 1);
+        survivors_before.push(0);
+        for new_index in &remap {
+            let survivors = survivors_before.last().copied().unwrap_or_default();
+            survivors_before.push(survivors + usize::from(new_index.is_some()));
+        }
+
         for value in stack {
             Self::update_value_handles(value, &remap);
         }
@@ -145,6 +193,21 @@ impl GC {
                 Self::update_value_handles(value, &remap);
             }
         }
+        for state in backup_states {
+            for value in &mut state.popped_values {
+                Self::update_value_handles(value, &remap);
+            }
+
+            state.heap_snapshots = std::mem::take(&mut state.heap_snapshots)
+                .into_iter()
+                .map(|(index, mut object)| {
+                    Self::update_object_handles(&mut object, &remap);
+                    (remap[index].unwrap(), object)
+                })
+                .collect();
+
+            state.heap_height = survivors_before[state.heap_height];
+        }
 
         self.alloc_count = 0;
     }
@@ -168,6 +231,25 @@ impl GC {
         }
     }
 
+    fn mark_object_contents(&mut self, object: &Object) {
+        match object {
+            Object::String(_) => {}
+            Object::Array(elements)
+            | Object::Enum {
+                fields: elements, ..
+            } => {
+                for value in elements {
+                    self.mark_value(value);
+                }
+            }
+            Object::Struct { fields, .. } => {
+                for value in fields.values() {
+                    self.mark_value(value);
+                }
+            }
+        }
+    }
+
     fn mark_object(&mut self, handle: Handle) {
         self.mark_stack.clear();
         self.mark_stack.push(handle);
@@ -182,7 +264,10 @@ impl GC {
 
             match &self.heap[index] {
                 Object::String(_) => {}
-                Object::Array(elements) => {
+                Object::Array(elements)
+                | Object::Enum {
+                    fields: elements, ..
+                } => {
                     for value in elements {
                         Self::collect_children(value, &mut self.mark_stack);
                     }
@@ -254,7 +339,10 @@ impl GC {
     fn update_object_handles(obj: &mut Object, remap: &[Option<usize>]) {
         match obj {
             Object::String(_) => {}
-            Object::Array(elements) => {
+            Object::Array(elements)
+            | Object::Enum {
+                fields: elements, ..
+            } => {
                 for value in elements {
                     Self::update_value_handles(value, remap);
                 }
@@ -1,12 +1,16 @@
 use ecow::EcoString;
 use std::collections::HashMap;
 
+use crate::ast::location::Location;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Instruction {
     PushInt(i64),
     PushFloat(f64),
     PushString(EcoString),
     PushChar(char),
+    PushBool(bool),
+    PushNil,
     PushArray(Vec<Value>),
 
     LoadToStack(EcoString),
@@ -17,21 +21,38 @@ pub enum Instruction {
     MulInt,
     DivInt,
     Mod,
+    NegateInt,
+
+    WrappingAddInt,
+    WrappingSubInt,
+    WrappingMulInt,
+    SaturatingAddInt,
+    SaturatingSubInt,
+    SaturatingMulInt,
+
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
 
     AddFloat,
     SubFloat,
     MulFloat,
     DivFloat,
+    NegateFloat,
 
     Append,
     Pop,
     GetByIndex,
     SetByIndex,
+    Length,
 
     Equal,
     NotEqual,
     And,
     Or,
+    Not,
 
     LessInt,
     LessEqualInt,
@@ -61,16 +82,26 @@ pub enum Instruction {
     SetField(EcoString),
     GetField(EcoString),
 
+    NewEnum(EcoString, EcoString, usize),
+    IsVariant(EcoString),
+    GetVariantField(usize),
+
     Print,
     Println,
 
     Backoff(EcoString),
+    Panic,
+    Exit,
 
     Halt,
 }
 
 pub type Bytecode = Vec<Instruction>;
 
+/// Location of the awa code, each instruction of the bytecode was produced from,
+/// indexed by program counter
+pub type Locations = Vec<Option<Location>>;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Handle(pub usize);
 
@@ -79,6 +110,7 @@ pub enum Value {
     Int(i64),
     Float(f64),
     Char(char),
+    Bool(bool),
     String(EcoString),
     Slice(Vec<Value>),
     Struct {
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use super::{instruction::Instruction, instruction::Value, VM};
+use super::{
+    error::RuntimeError, gc::Object, instruction::Instruction, instruction::Value, CodeLocation,
+    RunCommunication, StackFrame, VM,
+};
 #[test]
 fn test_push_load_store() {
     let bytecode = vec![
@@ -50,6 +53,35 @@ fn test_arithmetic_int() {
     }
 }
 
+#[test]
+fn test_constant_folding_keeps_unconsumed_constants() {
+    let body = vec![
+        Instruction::PushInt(1),
+        Instruction::PushInt(2),
+        Instruction::PushInt(3),
+        Instruction::AddInt,
+        Instruction::LessInt,
+        Instruction::Println,
+        Instruction::Return,
+    ];
+
+    let locations = vec![None; body.len()];
+
+    let (optimized, _) = crate::optimizer::Optimizer::optimize_function(body, locations, 0);
+
+    // NOTE: `1` is left for the comparison, which is not folded
+    assert_eq!(
+        optimized,
+        vec![
+            Instruction::PushInt(1),
+            Instruction::PushInt(5),
+            Instruction::LessInt,
+            Instruction::Println,
+            Instruction::Return,
+        ]
+    );
+}
+
 #[test]
 fn test_arithmetic_float() {
     let bytecode = vec![
@@ -77,6 +109,81 @@ fn test_arithmetic_float() {
     }
 }
 
+#[test]
+fn test_bitwise() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(12),
+        Instruction::PushInt(10),
+        Instruction::BitwiseAnd,
+        Instruction::PushInt(12),
+        Instruction::PushInt(10),
+        Instruction::BitwiseOr,
+        Instruction::PushInt(12),
+        Instruction::PushInt(10),
+        Instruction::BitwiseXor,
+        Instruction::PushInt(1),
+        Instruction::PushInt(4),
+        Instruction::ShiftLeft,
+        Instruction::PushInt(-16),
+        Instruction::PushInt(2),
+        Instruction::ShiftRight,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode.clone(), false);
+    for _i in bytecode {
+        let _ = vm.run();
+    }
+
+    assert_eq!(
+        vm.stack,
+        vec![
+            Value::Int(8),
+            Value::Int(14),
+            Value::Int(6),
+            Value::Int(16),
+            Value::Int(-4)
+        ]
+    );
+}
+
+#[test]
+fn test_unary() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(10),
+        Instruction::NegateInt,
+        Instruction::Println,
+        Instruction::PushFloat(2.5),
+        Instruction::NegateFloat,
+        Instruction::Println,
+        Instruction::PushBool(false),
+        Instruction::Not,
+        Instruction::Println,
+        Instruction::PushBool(true),
+        Instruction::Not,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode.clone(), false);
+    for _i in bytecode {
+        let _ = vm.run();
+    }
+
+    assert_eq!(
+        vm.stack,
+        vec![
+            Value::Int(-10),
+            Value::Float(-2.5),
+            Value::Bool(true),
+            Value::Bool(false)
+        ]
+    );
+}
+
 #[test]
 fn test_comparisons() {
     let bytecode = vec![
@@ -408,8 +515,11 @@ fn test_gc_local_alloc_print() {
         println!("  [{i}] {obj:?}");
     }
 
-    vm.gc
-        .collect_garbage(&mut vm.stack, &mut vm.environments_stack);
+    vm.gc.collect_garbage(
+        &mut vm.stack,
+        &mut vm.environments_stack,
+        &mut vm.backup_states,
+    );
 
     println!("Heap after manual GC:");
     for (i, obj) in vm.gc.heap.iter().enumerate() {
@@ -527,7 +637,7 @@ fn test_hotswap() {
         Instruction::EndFunc,
     ];
 
-    vm.hotswap_function(&new_code);
+    vm.hotswap_functions(&new_code, &[]);
 
     for _i in 1..=100 {
         let _ = vm.run();
@@ -564,3 +674,1328 @@ fn test_slice_2d() {
         let _ = vm.run();
     }
 }
+
+#[test]
+fn test_boolean() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(1),
+        Instruction::PushInt(2),
+        Instruction::LessInt,
+        Instruction::PushBool(false),
+        Instruction::Or,
+        Instruction::Println,
+        Instruction::PushBool(true),
+        Instruction::And,
+        Instruction::PushBool(true),
+        Instruction::Equal,
+        Instruction::PushFloat(1.0),
+        Instruction::PushFloat(2.0),
+        Instruction::GreaterFloat,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode.clone(), false);
+    for _i in bytecode {
+        let _ = vm.run();
+    }
+
+    assert_eq!(vm.stack, vec![Value::Bool(true), Value::Bool(false)]);
+}
+
+#[test]
+fn test_nil() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushNil,
+        Instruction::StoreInMap("head".into()),
+        Instruction::LoadToStack("head".into()),
+        Instruction::PushNil,
+        Instruction::Equal,
+        Instruction::LoadToStack("head".into()),
+        Instruction::PushInt(0),
+        Instruction::NotEqual,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode.clone(), false);
+    for _i in bytecode {
+        let _ = vm.run();
+    }
+
+    assert_eq!(vm.stack, vec![Value::Bool(true), Value::Bool(true)]);
+}
+
+#[test]
+fn test_enum() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushFloat(2.0),
+        Instruction::NewEnum("shape".into(), "circle".into(), 1),
+        Instruction::StoreInMap("s".into()),
+        Instruction::LoadToStack("s".into()),
+        Instruction::IsVariant("point".into()),
+        Instruction::LoadToStack("s".into()),
+        Instruction::IsVariant("circle".into()),
+        Instruction::LoadToStack("s".into()),
+        Instruction::GetVariantField(0),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode.clone(), false);
+    for _i in bytecode {
+        let _ = vm.run();
+    }
+
+    assert_eq!(
+        vm.stack,
+        vec![Value::Bool(false), Value::Bool(true), Value::Float(2.0)]
+    );
+}
+
+#[test]
+fn test_panic_and_exit() {
+    let location = crate::ast::location::Location { start: 0, end: 5 };
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushString("oops".into()),
+        Instruction::Panic,
+        Instruction::EndFunc,
+    ];
+    let locations = vec![None, None, Some(location), None];
+
+    let mut vm = VM::new_with_locations(bytecode, locations, false);
+    let _ = vm.run();
+
+    assert!(matches!(
+        vm.run(),
+        Some(RunCommunication::Panicked { message, location: panic_location, .. })
+            if message == "oops" && panic_location == Some(CodeLocation { source: 0, location })
+    ));
+
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(3),
+        Instruction::Exit,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let _ = vm.run();
+
+    assert!(matches!(vm.run(), Some(RunCommunication::Exited(3))));
+}
+
+#[test]
+fn test_exit_code_out_of_range() {
+    for code in [256, -1] {
+        let bytecode = vec![
+            Instruction::Func("main".into()),
+            Instruction::PushInt(code),
+            Instruction::Exit,
+            Instruction::EndFunc,
+        ];
+
+        let mut vm = VM::new(bytecode, false);
+        let _ = vm.run();
+
+        assert!(matches!(
+            vm.run(),
+            Some(RunCommunication::RuntimeError(RuntimeError::ExitCodeOutOfRange { code: found, .. }))
+                if found == code
+        ));
+    }
+}
+
+#[test]
+fn test_todo_backoff_and_hotswap() {
+    let bytecode = vec![
+        Instruction::Func("helper".into()),
+        Instruction::PushInt(1),
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("unfinished".into()),
+        Instruction::StoreInMap("x".into()),
+        Instruction::Call("helper".into()),
+        Instruction::StoreInMap("y".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::PushInt(5),
+        Instruction::Call("unfinished".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+    assert_eq!(vm.perform_backoff(), Some("unfinished".into()));
+    assert_eq!(vm.stack, vec![Value::Int(5)]);
+
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("unfinished".into()),
+            Instruction::StoreInMap("x".into()),
+            Instruction::LoadToStack("x".into()),
+            Instruction::PushInt(1),
+            Instruction::AddInt,
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[],
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack, vec![Value::Int(6)]);
+}
+
+#[test]
+fn test_hotswap_multiple_functions() {
+    let bytecode = vec![
+        Instruction::Func("helper".into()),
+        Instruction::PushInt(1),
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("sum".into()),
+        Instruction::StoreInMap("n".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::PushInt(3),
+        Instruction::Call("sum".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+    assert_eq!(vm.perform_backoff(), Some("sum".into()));
+
+    // NOTE: jumps of each function are relative to the whole fragment
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("helper".into()),
+            Instruction::PushInt(2),
+            Instruction::Return,
+            Instruction::EndFunc,
+            Instruction::Func("sum".into()),
+            Instruction::StoreInMap("n".into()),
+            Instruction::PushInt(0),
+            Instruction::StoreInMap("total".into()),
+            Instruction::LoadToStack("n".into()),
+            Instruction::PushInt(0),
+            Instruction::GreaterInt,
+            Instruction::JumpIfFalse(21),
+            Instruction::LoadToStack("total".into()),
+            Instruction::Call("helper".into()),
+            Instruction::AddInt,
+            Instruction::StoreInMap("total".into()),
+            Instruction::LoadToStack("n".into()),
+            Instruction::PushInt(1),
+            Instruction::SubInt,
+            Instruction::StoreInMap("n".into()),
+            Instruction::Jump(8),
+            Instruction::LoadToStack("total".into()),
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[],
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack, vec![Value::Int(6)]);
+    assert_eq!(vm.sources_count, 2);
+}
+
+#[test]
+fn test_hotswap_new_function() {
+    let bytecode = vec![
+        Instruction::Func("value".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::Call("value".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+    assert_eq!(vm.perform_backoff(), Some("value".into()));
+    assert!(!vm.functions.contains_key("helper"));
+
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("value".into()),
+            Instruction::Call("helper".into()),
+            Instruction::PushInt(1),
+            Instruction::AddInt,
+            Instruction::Return,
+            Instruction::EndFunc,
+            Instruction::Func("helper".into()),
+            Instruction::PushInt(41),
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[],
+    );
+
+    assert!(vm.functions.contains_key("helper"));
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack, vec![Value::Int(42)]);
+}
+
+#[test]
+fn test_hotswap_struct_migration() {
+    let bytecode = vec![
+        Instruction::Struct("Person".into()),
+        Instruction::Field("name".into(), Value::String("".into())),
+        Instruction::Field("age".into(), Value::Int(0)),
+        Instruction::EndStruct,
+        Instruction::Func("describe".into()),
+        Instruction::StoreInMap("person".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::NewStruct("Person".into()),
+        Instruction::PushInt(22),
+        Instruction::SetField("age".into()),
+        Instruction::Call("describe".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+    assert_eq!(vm.perform_backoff(), Some("describe".into()));
+
+    let new_code = [
+        Instruction::Struct("Person".into()),
+        Instruction::Field("age".into(), Value::Int(0)),
+        Instruction::Field("height".into(), Value::Int(170)),
+        Instruction::EndStruct,
+        Instruction::Func("describe".into()),
+        Instruction::StoreInMap("person".into()),
+        Instruction::LoadToStack("person".into()),
+        Instruction::GetField("age".into()),
+        Instruction::LoadToStack("person".into()),
+        Instruction::GetField("height".into()),
+        Instruction::AddInt,
+        Instruction::Return,
+        Instruction::EndFunc,
+    ];
+    vm.hotswap_structs(&new_code).unwrap();
+    vm.hotswap_functions(&new_code, &[]);
+
+    let Some(Object::Struct { fields, .. }) = vm.gc.heap.first() else {
+        panic!("expected struct on the heap");
+    };
+    assert_eq!(
+        *fields,
+        HashMap::from([
+            ("age".into(), Value::Int(22)),
+            ("height".into(), Value::Int(170)),
+        ])
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack, vec![Value::Int(192)]);
+}
+
+#[test]
+fn test_hotswap_struct_migration_allocates_new_fields() {
+    let bytecode = vec![
+        Instruction::Struct("Person".into()),
+        Instruction::Field("age".into(), Value::Int(0)),
+        Instruction::EndStruct,
+        Instruction::Func("describe".into()),
+        Instruction::StoreInMap("person".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::NewStruct("Person".into()),
+        Instruction::Call("describe".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+    assert_eq!(vm.perform_backoff(), Some("describe".into()));
+
+    let new_code = [
+        Instruction::Struct("Address".into()),
+        Instruction::Field("city".into(), Value::Int(7)),
+        Instruction::EndStruct,
+        Instruction::Struct("Person".into()),
+        Instruction::Field("age".into(), Value::Int(0)),
+        Instruction::Field("scores".into(), Value::Slice(Vec::new())),
+        Instruction::Field(
+            "address".into(),
+            Value::Struct {
+                name: "Address".into(),
+                fields: HashMap::new(),
+            },
+        ),
+        Instruction::EndStruct,
+        Instruction::Func("describe".into()),
+        Instruction::StoreInMap("person".into()),
+        Instruction::LoadToStack("person".into()),
+        Instruction::GetField("scores".into()),
+        Instruction::PushInt(5),
+        Instruction::Append,
+        Instruction::Length,
+        Instruction::LoadToStack("person".into()),
+        Instruction::GetField("scores".into()),
+        Instruction::Length,
+        Instruction::AddInt,
+        Instruction::LoadToStack("person".into()),
+        Instruction::GetField("address".into()),
+        Instruction::GetField("city".into()),
+        Instruction::AddInt,
+        Instruction::Return,
+        Instruction::EndFunc,
+    ];
+    vm.hotswap_structs(&new_code).unwrap();
+    vm.hotswap_functions(&new_code, &[]);
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack, vec![Value::Int(9)]);
+}
+
+#[test]
+fn test_failed_hotswap_struct_migration_leaves_instances() {
+    let bytecode = vec![
+        Instruction::Struct("Person".into()),
+        Instruction::Field("age".into(), Value::Int(0)),
+        Instruction::EndStruct,
+        Instruction::Func("main".into()),
+        Instruction::NewStruct("Person".into()),
+        Instruction::StoreInMap("first".into()),
+        Instruction::NewStruct("Person".into()),
+        Instruction::StoreInMap("second".into()),
+        Instruction::NewStruct("Person".into()),
+        Instruction::StoreInMap("third".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    vm.set_max_heap_size(5);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+
+    // NOTE: each of the 3 live instances needs its own array, which does not fit
+    let new_code = [
+        Instruction::Struct("Person".into()),
+        Instruction::Field("age".into(), Value::Int(0)),
+        Instruction::Field("scores".into(), Value::Slice(Vec::new())),
+        Instruction::EndStruct,
+    ];
+    assert!(matches!(
+        vm.hotswap_structs(&new_code),
+        Err(RuntimeError::HeapLimitExceeded { limit: 5, .. })
+    ));
+
+    assert_eq!(
+        vm.structures.get("Person"),
+        Some(&HashMap::from([("age".into(), Value::Int(0))]))
+    );
+    assert_eq!(vm.gc.heap.len(), 3);
+    for object in &vm.gc.heap {
+        let Object::Struct { fields, .. } = object else {
+            panic!("expected struct on the heap");
+        };
+        assert_eq!(*fields, HashMap::from([("age".into(), Value::Int(0))]));
+    }
+}
+
+#[test]
+fn test_runtime_error_locations() {
+    let division = crate::ast::location::Location { start: 10, end: 15 };
+    let bytecode = vec![
+        Instruction::Func("divide".into()),
+        Instruction::PushInt(0),
+        Instruction::DivInt,
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::PushInt(1),
+        Instruction::Call("divide".into()),
+        Instruction::PushInt(0),
+        Instruction::DivInt,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+    let mut locations = vec![None; bytecode.len()];
+    locations[2] = Some(division);
+    locations[9] = Some(division);
+
+    let mut vm = VM::new_with_locations(bytecode, locations, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected runtime error, found {communication:?}");
+    };
+    assert_eq!(
+        vm.location_at(error.get_program_counter()),
+        Some(CodeLocation {
+            source: 0,
+            location: division
+        })
+    );
+    assert_eq!(vm.perform_backoff(), Some("divide".into()));
+
+    // NOTE: locations of the hotswapped code refer to its own source
+    let swapped_division = crate::ast::location::Location { start: 20, end: 25 };
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("divide".into()),
+            Instruction::PushInt(0),
+            Instruction::DivInt,
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[None, None, Some(swapped_division), None, None],
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected runtime error, found {communication:?}");
+    };
+    assert_eq!(
+        vm.location_at(error.get_program_counter()),
+        Some(CodeLocation {
+            source: 1,
+            location: swapped_division
+        })
+    );
+    assert_eq!(vm.perform_backoff(), Some("divide".into()));
+
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("divide".into()),
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[],
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected runtime error, found {communication:?}");
+    };
+    assert_eq!(
+        vm.location_at(error.get_program_counter()),
+        Some(CodeLocation {
+            source: 0,
+            location: division
+        })
+    );
+    // NOTE: failure in `main()` cannot be rolled back
+    assert_eq!(vm.perform_backoff(), None);
+}
+
+#[test]
+fn test_locations_after_optimization() {
+    let loop_location = crate::ast::location::Location { start: 0, end: 5 };
+    let division = crate::ast::location::Location { start: 10, end: 15 };
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(0),
+        Instruction::StoreInMap("i".into()),
+        Instruction::LoadToStack("i".into()),
+        Instruction::PushInt(20000),
+        Instruction::LessInt,
+        Instruction::JumpIfFalse(16),
+        Instruction::PushInt(2),
+        Instruction::PushInt(3),
+        Instruction::AddInt,
+        Instruction::StoreInMap("x".into()),
+        Instruction::LoadToStack("i".into()),
+        Instruction::PushInt(1),
+        Instruction::AddInt,
+        Instruction::StoreInMap("i".into()),
+        Instruction::Jump(3),
+        Instruction::PushInt(1),
+        Instruction::PushInt(0),
+        Instruction::DivInt,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+    let mut locations = vec![Some(loop_location); bytecode.len()];
+    locations[18] = Some(division);
+
+    let mut vm = VM::new_with_locations(bytecode, locations, true);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(vm.input.len() < 21);
+    assert_eq!(vm.input.len(), vm.locations.len());
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::DivisionByZero { .. })
+    ));
+    assert_eq!(
+        vm.current_location(),
+        Some(CodeLocation {
+            source: 0,
+            location: division
+        })
+    );
+}
+
+#[test]
+fn test_backtrace() {
+    let call = crate::ast::location::Location { start: 0, end: 5 };
+    let todo = crate::ast::location::Location { start: 10, end: 15 };
+    let bytecode = vec![
+        Instruction::Func("unfinished".into()),
+        Instruction::StoreInMap("x".into()),
+        Instruction::Backoff("reached todo".into()),
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::PushString("awa".into()),
+        Instruction::StoreInMap("name".into()),
+        Instruction::PushInt(5),
+        Instruction::Call("unfinished".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+    let mut locations = vec![None; bytecode.len()];
+    locations[2] = Some(todo);
+    locations[8] = Some(call);
+
+    let mut vm = VM::new_with_locations(bytecode, locations, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff { .. })
+    ));
+    assert_eq!(
+        vm.backtrace(),
+        vec![
+            StackFrame {
+                function_name: "unfinished".into(),
+                location: Some(CodeLocation {
+                    source: 0,
+                    location: todo
+                }),
+                locals: vec![("x".into(), "5".into())],
+            },
+            StackFrame {
+                function_name: "main".into(),
+                location: Some(CodeLocation {
+                    source: 0,
+                    location: call
+                }),
+                locals: vec![("name".into(), "awa".into())],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_runtime_errors() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::Call("missing".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = vm.run();
+
+    assert!(matches!(
+        communication,
+        Some(RunCommunication::RuntimeError(RuntimeError::UndefinedFunction {
+            program_counter: 1,
+            function_name,
+        })) if function_name == "missing"
+    ));
+
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(1),
+        Instruction::AddInt,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let _ = vm.run();
+
+    assert!(matches!(
+        vm.run(),
+        Some(RunCommunication::RuntimeError(
+            RuntimeError::StackUnderflow { program_counter: 2 }
+        ))
+    ));
+}
+
+#[test]
+fn test_backoff_restores_stack() {
+    let bytecode = vec![
+        Instruction::Func("take".into()),
+        Instruction::StoreInMap("array".into()),
+        Instruction::LoadToStack("array".into()),
+        Instruction::Pop,
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::PushInt(7),
+        Instruction::PushArray(Vec::new()),
+        Instruction::Call("take".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected runtime error, found {communication:?}");
+    };
+    assert_eq!(
+        error,
+        RuntimeError::PopFromEmptyArray { program_counter: 3 }
+    );
+    assert!(error.is_recoverable());
+
+    assert_eq!(vm.stack, vec![Value::Int(7)]);
+
+    // NOTE: array argument is back on the stack, so that the call can be repeated
+    assert_eq!(vm.perform_backoff(), Some("take".into()));
+    assert!(matches!(
+        vm.stack.as_slice(),
+        [Value::Int(7), Value::Ref(_)]
+    ));
+    assert_eq!(vm.program_counter, 9);
+    assert!(vm.call_stack.is_empty());
+    assert_eq!(vm.environments_stack.len(), 1);
+
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("take".into()),
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[],
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack.len(), 2);
+}
+
+#[test]
+fn test_backoff_to_outer_frame() {
+    let bytecode = vec![
+        Instruction::Func("inner".into()),
+        Instruction::StoreInMap("array".into()),
+        Instruction::LoadToStack("array".into()),
+        Instruction::PushInt(2),
+        Instruction::Append,
+        Instruction::Backoff("unfinished".into()),
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("outer".into()),
+        Instruction::StoreInMap("array".into()),
+        Instruction::LoadToStack("array".into()),
+        Instruction::PushInt(1),
+        Instruction::Append,
+        Instruction::Call("inner".into()),
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::PushInt(7),
+        Instruction::PushArray(vec![Value::Int(0)]),
+        Instruction::Call("outer".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    vm.set_heap_snapshots(true);
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(
+        communication,
+        RunCommunication::RuntimeError(RuntimeError::Backoff {
+            program_counter: 5,
+            ..
+        })
+    ));
+    assert_eq!(vm.backtrace().len(), 3);
+
+    // NOTE: both appends are undone, as `outer` is called again from the start
+    assert_eq!(vm.perform_backoff_to(1), Some("outer".into()));
+    let [Value::Int(7), Value::Ref(handle)] = vm.stack.as_slice() else {
+        panic!("expected arguments of `outer`, found {:?}", vm.stack);
+    };
+    let handle = *handle;
+    assert!(matches!(
+        vm.gc.get(handle),
+        Object::Array(elements) if elements == &[Value::Int(0)]
+    ));
+    assert_eq!(vm.program_counter, 19);
+    assert!(vm.call_stack.is_empty());
+    assert!(vm.backup_states.is_empty());
+    assert_eq!(vm.environments_stack.len(), 1);
+
+    vm.hotswap_functions(
+        &[
+            Instruction::Func("inner".into()),
+            Instruction::StoreInMap("array".into()),
+            Instruction::Return,
+            Instruction::EndFunc,
+        ],
+        &[],
+    );
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert!(matches!(
+        vm.gc.get(handle),
+        Object::Array(elements) if elements == &[Value::Int(0), Value::Int(1)]
+    ));
+}
+
+#[test]
+fn test_integer_overflow() {
+    let overflows = [
+        (Instruction::AddInt, i64::MAX, 1),
+        (Instruction::SubInt, i64::MIN, 1),
+        (Instruction::MulInt, i64::MAX, 2),
+        (Instruction::DivInt, i64::MIN, -1),
+        (Instruction::Mod, i64::MIN, -1),
+    ];
+
+    for (instruction, lhs, rhs) in overflows {
+        let bytecode = vec![
+            Instruction::Func("main".into()),
+            Instruction::PushInt(lhs),
+            Instruction::PushInt(rhs),
+            instruction.clone(),
+            Instruction::Halt,
+            Instruction::EndFunc,
+        ];
+
+        let mut vm = VM::new(bytecode, false);
+        let communication = loop {
+            if let Some(communication) = vm.run() {
+                break communication;
+            }
+        };
+
+        let RunCommunication::RuntimeError(error) = communication else {
+            panic!("expected overflow of {instruction:?}, found {communication:?}");
+        };
+        assert!(matches!(
+            error,
+            RuntimeError::IntegerOverflow {
+                program_counter: 3,
+                ..
+            }
+        ));
+        assert!(error.is_recoverable());
+    }
+
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(i64::MIN),
+        Instruction::NegateInt,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected overflow of negation, found {communication:?}");
+    };
+    assert_eq!(
+        error.get_description(),
+        "integer overflow in `-(-9223372036854775808)`"
+    );
+}
+
+#[test]
+fn test_wrapping_and_saturating_arithmetic() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(i64::MAX),
+        Instruction::PushInt(1),
+        Instruction::WrappingAddInt,
+        Instruction::PushInt(i64::MIN),
+        Instruction::PushInt(1),
+        Instruction::WrappingSubInt,
+        Instruction::PushInt(i64::MAX),
+        Instruction::PushInt(2),
+        Instruction::WrappingMulInt,
+        Instruction::PushInt(i64::MAX),
+        Instruction::PushInt(1),
+        Instruction::SaturatingAddInt,
+        Instruction::PushInt(i64::MIN),
+        Instruction::PushInt(1),
+        Instruction::SaturatingSubInt,
+        Instruction::PushInt(i64::MAX),
+        Instruction::PushInt(-2),
+        Instruction::SaturatingMulInt,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(
+        vm.stack,
+        vec![
+            Value::Int(i64::MIN),
+            Value::Int(i64::MAX),
+            Value::Int(-2),
+            Value::Int(i64::MAX),
+            Value::Int(i64::MIN),
+            Value::Int(i64::MIN),
+        ]
+    );
+}
+
+#[test]
+fn test_constant_folding_keeps_overflow() {
+    let body = vec![
+        Instruction::PushInt(i64::MAX),
+        Instruction::PushInt(1),
+        Instruction::AddInt,
+        Instruction::Println,
+        Instruction::PushInt(i64::MAX),
+        Instruction::PushInt(1),
+        Instruction::WrappingAddInt,
+        Instruction::Println,
+        Instruction::Return,
+    ];
+    let locations = vec![None; body.len()];
+
+    let (optimized, _) = crate::optimizer::Optimizer::optimize_function(body, locations, 0);
+
+    // NOTE: overflowing addition is left for VM to report, wrapping one is folded
+    assert_eq!(
+        optimized,
+        vec![
+            Instruction::PushInt(i64::MAX),
+            Instruction::PushInt(1),
+            Instruction::AddInt,
+            Instruction::Println,
+            Instruction::PushInt(i64::MIN),
+            Instruction::Println,
+            Instruction::Return,
+        ]
+    );
+}
+
+#[test]
+fn test_stack_overflow() {
+    let bytecode = vec![
+        Instruction::Func("rec".into()),
+        Instruction::Call("rec".into()),
+        Instruction::Return,
+        Instruction::EndFunc,
+        Instruction::Func("main".into()),
+        Instruction::Call("rec".into()),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    vm.set_max_call_depth(3);
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected stack overflow, found {communication:?}");
+    };
+    assert_eq!(
+        error,
+        RuntimeError::StackOverflow {
+            program_counter: 1,
+            depth: 3
+        }
+    );
+    assert!(error.is_recoverable());
+    assert_eq!(vm.backtrace().len(), 4);
+
+    // NOTE: the outermost recursive call is restarted from `main()`
+    assert_eq!(vm.perform_backoff_to(2), Some("rec".into()));
+    assert_eq!(vm.program_counter, 5);
+    assert!(vm.call_stack.is_empty());
+    assert_eq!(vm.environments_stack.len(), 1);
+}
+
+#[test]
+fn test_fuel() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushInt(1),
+        Instruction::PushInt(2),
+        Instruction::AddInt,
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    vm.set_fuel(2);
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected running out of fuel, found {communication:?}");
+    };
+    assert_eq!(error, RuntimeError::OutOfFuel { program_counter: 3 });
+    assert!(!error.is_recoverable());
+
+    // NOTE: execution goes on from the same instruction with more fuel
+    vm.set_fuel(10);
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(matches!(communication, RunCommunication::Finished));
+    assert_eq!(vm.stack, vec![Value::Int(3)]);
+}
+
+#[test]
+fn test_heap_limit() {
+    let bytecode = vec![
+        Instruction::Func("main".into()),
+        Instruction::PushArray(Vec::new()),
+        Instruction::StoreInMap("array".into()),
+        Instruction::PushInt(0),
+        Instruction::StoreInMap("i".into()),
+        // NOTE: garbage strings are collected, once the heap is full
+        Instruction::LoadToStack("i".into()),
+        Instruction::PushInt(100),
+        Instruction::LessInt,
+        Instruction::JumpIfFalse(16),
+        Instruction::PushString("garbage".into()),
+        Instruction::StoreInMap("garbage".into()),
+        Instruction::LoadToStack("i".into()),
+        Instruction::PushInt(1),
+        Instruction::AddInt,
+        Instruction::StoreInMap("i".into()),
+        Instruction::Jump(5),
+        // NOTE: live strings are kept, until they do not fit
+        Instruction::LoadToStack("array".into()),
+        Instruction::PushString("live".into()),
+        Instruction::Append,
+        Instruction::Jump(16),
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    vm.set_max_heap_size(10);
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    let RunCommunication::RuntimeError(error) = communication else {
+        panic!("expected exceeded heap limit, found {communication:?}");
+    };
+    assert_eq!(
+        error,
+        RuntimeError::HeapLimitExceeded {
+            program_counter: 17,
+            limit: 10
+        }
+    );
+    assert!(!error.is_recoverable());
+    assert!(vm.gc.heap.len() <= 10);
+}
+
+#[test]
+fn test_heap_limit_collects_garbage_before_failing() {
+    let bytecode = vec![
+        Instruction::Struct("inner".into()),
+        Instruction::Field("values".into(), Value::Slice(Vec::new())),
+        Instruction::EndStruct,
+        Instruction::Struct("outer".into()),
+        Instruction::Field(
+            "inner".into(),
+            Value::Struct {
+                name: "inner".into(),
+                fields: HashMap::new(),
+            },
+        ),
+        Instruction::Field("items".into(), Value::Slice(Vec::new())),
+        Instruction::EndStruct,
+        Instruction::Func("main".into()),
+        Instruction::PushInt(0),
+        Instruction::StoreInMap("i".into()),
+        Instruction::LoadToStack("i".into()),
+        Instruction::PushInt(50),
+        Instruction::LessInt,
+        Instruction::JumpIfFalse(21),
+        // NOTE: each instance takes 4 objects, so at most 8 of them are live
+        Instruction::NewStruct("outer".into()),
+        Instruction::StoreInMap("current".into()),
+        Instruction::LoadToStack("i".into()),
+        Instruction::PushInt(1),
+        Instruction::AddInt,
+        Instruction::StoreInMap("i".into()),
+        Instruction::Jump(10),
+        Instruction::Halt,
+        Instruction::EndFunc,
+    ];
+
+    let mut vm = VM::new(bytecode, false);
+    vm.set_max_heap_size(10);
+
+    let communication = loop {
+        if let Some(communication) = vm.run() {
+            break communication;
+        }
+    };
+
+    assert!(
+        matches!(communication, RunCommunication::Finished),
+        "expected finish, found {communication:?}"
+    );
+    let Some(Value::Ref(current)) = vm.environments_stack.last().unwrap().get("current") else {
+        panic!("expected the last instance to be live");
+    };
+    let Object::Struct { fields, .. } = vm.gc.get(*current) else {
+        panic!("expected struct on the heap");
+    };
+    assert!(matches!(fields.get("items"), Some(Value::Ref(_))));
+}
+
+/// Runs awa program through the whole pipeline, until it stops
+fn run_source(src: &str) -> RunCommunication {
+    let module = crate::ast::analyzer::TypeAnalyzer::new()
+        .analyze_input(src)
+        .unwrap();
+    let (bytecode, locations) = crate::interpreter::Interpreter::new().interpret_module(&module);
+
+    let mut vm = VM::new_with_locations(bytecode, locations, false);
+    loop {
+        if let Some(communication) = vm.run() {
+            return communication;
+        }
+    }
+}
+
+#[test]
+fn test_nested_for_loops_over_same_variable() {
+    let communication = run_source(
+        "func main() {
+            var count int = 0
+            for i in 0..3 {
+                for i in 10..12 {
+                    count = count + 1
+                }
+            }
+            var xs []int = []int{1, 2}
+            for x in xs {
+                for x in xs {
+                    count = count + x
+                }
+            }
+            exit(count)
+        }",
+    );
+
+    assert!(matches!(communication, RunCommunication::Exited(12)));
+}
+
+#[test]
+fn test_continue_in_loops() {
+    let communication = run_source(
+        "func main() {
+            var count int = 0
+            var i int = 0
+            loop (i < 10) {
+                i = i + 1
+                if (i % 2 == 0) {
+                    continue
+                }
+                count = count + 1
+            }
+            exit(count)
+        }",
+    );
+    assert!(matches!(communication, RunCommunication::Exited(5)));
+
+    // NOTE: `continue` in `for` still steps the index, otherwise the loop would not end
+    let communication = run_source(
+        "func main() {
+            var count int = 0
+            for i in 0..10 {
+                if (i < 7) {
+                    continue
+                }
+                count = count + i
+            }
+            var xs []int = []int{1, 2, 3, 4}
+            for x in xs {
+                if (x == 2) {
+                    continue
+                }
+                count = count + x
+            }
+            exit(count)
+        }",
+    );
+    assert!(matches!(communication, RunCommunication::Exited(32)));
+}